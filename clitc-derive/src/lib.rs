@@ -0,0 +1,348 @@
+#![allow(clippy::needless_return)]
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use serde_json::{json, Map, Value};
+use syn::{
+    parse_macro_input,
+    spanned::Spanned,
+    Data,
+    DeriveInput,
+    Error,
+    Fields,
+    GenericArgument,
+    Ident,
+    LitInt,
+    LitStr,
+    PathArguments,
+    Type,
+};
+
+/// Generates `spec_json`, `cli_parameters`, `from_parse_result` and `parse_args` for a struct.
+///
+/// Every field is either a flag (`bool`), a subparam of an option or a positional.
+/// Fields sharing the same option `name` become subparams of the same option, ordered by `ord`.
+/// Fields which are not wrapped in an `Option` are required.
+///
+/// ```ignore
+/// #[derive(Clitc)]
+/// struct Args {
+///     #[clitc(short = "-v", descr = "Print more", env = "APP_VERBOSE")]
+///     verbose: bool,
+///     #[clitc(name = "--lifetime", short = "-l", param = "secs", ord = 0)]
+///     secs: Option<i64>,
+///     #[clitc(name = "--lifetime", param = "expected_val", ord = 1)]
+///     expected_val: Option<f64>,
+///     #[clitc(positional)]
+///     input: String,
+/// }
+/// ```
+#[proc_macro_derive(Clitc, attributes(clitc))]
+pub fn derive_clitc(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    name: Option<String>,
+    short: Option<String>,
+    descr: Option<String>,
+    ptype: Option<String>,
+    ord: Option<u8>,
+    param: Option<String>,
+    env: Option<String>,
+    group: Option<String>,
+    positional: bool,
+}
+
+impl FieldAttrs {
+    fn from(field: &syn::Field) -> Result<FieldAttrs, Error> {
+        let mut attrs = FieldAttrs::default();
+        for attr in field.attrs.iter() {
+            if !attr.path().is_ident("clitc") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    attrs.name = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("short") {
+                    attrs.short = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("descr") {
+                    attrs.descr = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("type") {
+                    attrs.ptype = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("ord") {
+                    attrs.ord = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                } else if meta.path.is_ident("param") {
+                    attrs.param = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("env") {
+                    attrs.env = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("group") {
+                    attrs.group = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("positional") {
+                    attrs.positional = true;
+                } else {
+                    return Err(meta.error("unsupported clitc attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        return Ok(attrs);
+    }
+}
+
+enum Kind {
+    /// Option without subparams, true if present
+    Flag,
+    /// Value of a subparam or positional
+    Value { ty: Box<Type>, optional: bool, ptype: String },
+}
+
+struct FieldSpec {
+    ident: Ident,
+    attrs: FieldAttrs,
+    kind: Kind,
+}
+
+fn type_string(ty: &Type) -> String {
+    return ty.to_token_stream().to_string().replace(' ', "");
+}
+
+/// Inner type of an `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    if let Type::Path(path) = ty {
+        let segment = path.path.segments.last()?;
+        if segment.ident != "Option" {
+            return None;
+        }
+        if let PathArguments::AngleBracketed(args) = &segment.arguments {
+            if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                return Some(inner);
+            }
+        }
+    }
+    return None;
+}
+
+/// Subparam type matching a rust type
+fn infer_type(ty: &Type) -> Option<&'static str> {
+    match &type_string(ty)[..] {
+        "i64" => Some("int"),
+        "f64" => Some("num"),
+        "String" => Some("string"),
+        "Vec<String>" => Some("array"),
+        "u64" => Some("uint"),
+        "char" => Some("char"),
+        "PathBuf" | "std::path::PathBuf" => Some("path"),
+        "Duration" | "std::time::Duration" => Some("duration"),
+        _ => None,
+    }
+}
+
+impl FieldSpec {
+    fn from(field: &syn::Field) -> Result<FieldSpec, Error> {
+        let ident = field.ident.clone().ok_or_else(|| Error::new(field.span(), "clitc fields must be named"))?;
+        let attrs = FieldAttrs::from(field)?;
+
+        let kind = if type_string(&field.ty) == "bool" {
+            if attrs.positional || attrs.param.is_some() || attrs.ptype.is_some() {
+                return Err(Error::new(field.ty.span(), "bool fields are flags and cannot take a value"));
+            }
+            Kind::Flag
+        } else {
+            let (ty, optional) = match option_inner(&field.ty) {
+                Some(inner) => (inner.clone(), true),
+                None => (field.ty.clone(), false),
+            };
+            let inferred = infer_type(&ty).ok_or_else(|| {
+                Error::new(ty.span(), "unsupported field type, expected bool, i64, u64, f64, char, String, Vec<String>, PathBuf or Duration")
+            })?;
+            let ptype = match &attrs.ptype {
+                // Urls are validated strings
+                Some(ptype) if ptype == inferred || (ptype == "url" && inferred == "string") => ptype.clone(),
+                Some(ptype) => {
+                    return Err(Error::new(ty.span(), format!("field of type {} cannot hold a subparam of type {}", type_string(&ty), ptype)));
+                },
+                None => inferred.to_string(),
+            };
+            Kind::Value { ty: Box::new(ty), optional, ptype }
+        };
+
+        return Ok(FieldSpec{ident, attrs, kind});
+    }
+
+    fn option_name(&self) -> String {
+        match &self.attrs.name {
+            Some(name) => name.clone(),
+            None => format!("--{}", self.ident.to_string().replace('_', "-")),
+        }
+    }
+
+    fn param_name(&self) -> String {
+        match &self.attrs.param {
+            Some(param) => param.clone(),
+            None => self.ident.to_string(),
+        }
+    }
+
+    fn required(&self) -> bool {
+        match &self.kind {
+            Kind::Flag => false,
+            Kind::Value { optional, .. } => !optional,
+        }
+    }
+
+    fn subparam_json(&self, ord: u8) -> Value {
+        let ptype = match &self.kind {
+            Kind::Value { ptype, .. } => ptype.clone(),
+            Kind::Flag => unreachable!(),
+        };
+        let mut subparam = json!({
+            "ord": self.attrs.ord.unwrap_or(ord),
+            "name": self.param_name(),
+            "type": ptype,
+            "required": self.required(),
+        });
+        if let Some(env) = &self.attrs.env {
+            subparam["env"] = Value::from(env.clone());
+        }
+        return subparam;
+    }
+
+    fn value_expr(&self) -> TokenStream2 {
+        let (ty, optional) = match &self.kind {
+            Kind::Flag => {
+                let name = self.option_name();
+                return quote! { res.is_present(#name) };
+            },
+            Kind::Value { ty, optional, .. } => (ty, *optional),
+        };
+        let param = self.param_name();
+        let (get, missing) = if self.attrs.positional {
+            (
+                quote! { res.positional::<#ty>(#param)? },
+                quote! { (::std::string::String::from(#param), ::std::option::Option::None) },
+            )
+        } else {
+            let name = self.option_name();
+            (
+                quote! { res.get::<#ty>(#name, #param)? },
+                quote! { (::std::string::String::from(#name), ::std::option::Option::Some(::std::string::String::from(#param))) },
+            )
+        };
+        if optional {
+            return get;
+        }
+        return quote! {
+            #get.ok_or_else(|| ::clitc::clitc_error::ClitcError::MissingRequired { missing: vec![#missing] })?
+        };
+    }
+}
+
+/// Builds the JSON spec for the existing loaders
+fn spec_json(fields: &[FieldSpec]) -> Result<String, Error> {
+    // Options in order of first appearance
+    let mut options: Vec<(String, Map<String, Value>, Vec<Value>)> = Vec::new();
+    let mut positionals = Vec::new();
+
+    for field in fields.iter() {
+        if field.attrs.positional {
+            positionals.push(field.subparam_json(positionals.len() as u8));
+            continue;
+        }
+        let name = field.option_name();
+        let index = match options.iter().position(|x| x.0 == name) {
+            Some(index) => index,
+            None => {
+                let mut option = Map::new();
+                option.insert(String::from("name"), Value::from(name.clone()));
+                options.push((name, option, Vec::new()));
+                options.len() - 1
+            },
+        };
+        let (_, option, params) = &mut options[index];
+        for (key, val) in [("short", &field.attrs.short), ("descr", &field.attrs.descr), ("group", &field.attrs.group)] {
+            if let Some(val) = val {
+                if let Some(prev) = option.insert(String::from(key), Value::from(val.clone())) {
+                    if prev != *val {
+                        return Err(Error::new(field.ident.span(), format!("conflicting {} for option {}", key, option["name"])));
+                    }
+                }
+            }
+        }
+        if field.required() {
+            option.insert(String::from("required"), Value::from(true));
+        }
+        match field.kind {
+            Kind::Value { .. } => params.push(field.subparam_json(params.len() as u8)),
+            // Environment variables of flags belong to the option
+            Kind::Flag => if let Some(env) = &field.attrs.env {
+                option.insert(String::from("env"), Value::from(env.clone()));
+            },
+        }
+    }
+
+    let options: Vec<Value> = options.into_iter().map(|(_, mut option, params)| {
+        option.insert(String::from("params"), Value::from(params));
+        Value::Object(option)
+    }).collect();
+    let spec = json!({
+        "options": options,
+        "positionals": positionals,
+    });
+    return Ok(serde_json::to_string_pretty(&spec).unwrap());
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(input.generics.span(), "Clitc cannot be derived for generic structs"));
+    }
+    let named = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(named) => &named.named,
+            _ => return Err(Error::new(input.ident.span(), "Clitc can only be derived for structs with named fields")),
+        },
+        _ => return Err(Error::new(input.ident.span(), "Clitc can only be derived for structs")),
+    };
+
+    let mut fields = Vec::new();
+    for field in named.iter() {
+        fields.push(FieldSpec::from(field)?);
+    }
+    let spec = spec_json(&fields)?;
+
+    let ident = &input.ident;
+    let idents = fields.iter().map(|x| &x.ident);
+    let values = fields.iter().map(|x| x.value_expr());
+
+    return Ok(quote! {
+        impl #ident {
+            /// JSON spec of the options and positionals of this struct
+            pub fn spec_json() -> &'static str {
+                #spec
+            }
+
+            pub fn cli_parameters() -> ::std::result::Result<::clitc::params::CliParameters, ::clitc::clitc_error::ClitcError> {
+                ::clitc::params::CliParameters::from_str(Self::spec_json())
+            }
+
+            pub fn from_parse_result(res: &::clitc::params::ParseResult) -> ::std::result::Result<Self, ::clitc::clitc_error::ClitcError> {
+                ::std::result::Result::Ok(#ident {
+                    #(#idents: #values,)*
+                })
+            }
+
+            pub fn parse_args() -> ::std::result::Result<Self, ::clitc::clitc_error::ClitcError> {
+                let res = Self::cli_parameters()?.parse_args()?;
+                Self::from_parse_result(&res)
+            }
+        }
+    });
+}
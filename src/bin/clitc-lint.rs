@@ -0,0 +1,52 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+use std::{env, fs, process};
+use clitc::lint;
+
+/// Checks spec files against the spec schema and semantic rules, printing every problem
+fn main() {
+    let mut files = Vec::new();
+    let mut custom_types = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--type" {
+            match args.next() {
+                Some(custom_type) => custom_types.push(custom_type),
+                None => {
+                    eprintln!("--type needs the name of a custom type");
+                    process::exit(2);
+                },
+            }
+        } else {
+            files.push(arg);
+        }
+    }
+    if files.is_empty() {
+        eprintln!("Usage: clitc-lint [--type <custom type>]... <spec.json>...");
+        process::exit(2);
+    }
+
+    let mut failed = false;
+    for file in files.iter() {
+        let data = match fs::read_to_string(file) {
+            Ok(data) => data,
+            Err(err) => {
+                println!("{}: {}", file, err);
+                failed = true;
+                continue;
+            },
+        };
+        for problem in lint::lint_str(&data, &custom_types) {
+            failed = true;
+            match problem.line {
+                Some(line) => println!("{}:{}: {}", file, line, problem),
+                None => println!("{}: {}", file, problem),
+            }
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
@@ -3,98 +3,208 @@ use std::{
     fmt::{Display, Formatter, Result},
 };
 
-#[derive(Clone, Debug)]
-pub struct MissingInformationError;
-
-impl Error for MissingInformationError {
-    fn description(&self) -> &str {
-        "Necessary information for Param missing!"
-    }
-
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        None
-    }
+#[derive(Debug)]
+pub enum ClitcError {
+    /// Spec file could not be read
+    Io(std::io::Error),
+    /// Spec could not be read as JSON
+    Json(serde_json::Error),
+    /// Spec could not be read as TOML
+    #[cfg(feature = "toml")]
+    Toml(toml::de::Error),
+    /// Spec could not be read as YAML
+    #[cfg(feature = "yaml")]
+    Yaml(serde_yaml::Error),
+    /// Necessary information at the given JSON path is missing
+    MissingInformation { path: String },
+    /// Information at the given JSON path has the wrong format
+    WrongFormat { path: String, reason: String },
+    /// Two subparams of the same option share an ord
+    DuplicateOrd { option: String, ord: u8 },
+    /// Short or name of an option is already used by another option
+    ConflictingIdentifier { path: String, identifier: String, other: String },
+    /// Name of an option is the prefix of another name, so abbreviating it is always ambiguous
+    AmbiguousPrefix { path: String, prefix: String, identifier: String, other: String },
+    /// Abbreviated option matches the names of several options
+    AmbiguousOption { token: String, index: usize, candidates: Vec<String> },
+    /// Multiple commands entered, but single command mode configured
+    CommandMode { commands: Vec<String> },
+    /// Input of a command ends inside of a quoted string, which started at the given byte position
+    UnterminatedQuote { quote: char, position: usize },
+    /// No event attached to the command
+    NoEvent { command: String },
+    /// None of the tokens matched a known command
+    UnknownCommand { tokens: Vec<String> },
+    /// Token could not be parsed as the type of its subparam
+    InvalidValue {
+        option: String,
+        subparam: String,
+        index: usize,
+        raw: String,
+        expected: String,
+    },
+    /// Value does not fulfil a constraint of its subparam, e.g. `max`
+    ConstraintViolation {
+        option: String,
+        subparam: String,
+        index: usize,
+        raw: String,
+        reason: String,
+    },
+    /// No `ValueParser` is registered for the custom type of a subparam
+    UnknownType { name: String },
+    /// Value of an environment variable does not fit its option or subparam
+    InvalidEnv { var: String, reason: String },
+    /// Value of a config file does not fit its option or subparam, or the file could not be read
+    InvalidConfig { file: String, key: String, reason: String },
+    /// Required options or subparams (option, subparam) were not supplied
+    MissingRequired { missing: Vec<(String, Option<String>)> },
+    /// Number of tokens for a positional does not fit its arity
+    WrongArity { name: String, expected: String, found: usize },
+    /// Help was requested by `--help`, `-h` or `help <topic>`, displays the rendered help page
+    HelpRequested { help: String },
+    /// Requested type of a value does not match the declared type of its subparam
+    TypeMismatch { name: String, expected: String, found: String },
 }
 
-impl Display for MissingInformationError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "Necessary information for Param missing!")
+impl ClitcError {
+    /// Prefixes the JSON paths of spec errors with the location of the enclosing element
+    pub fn within(self, prefix: &str) -> ClitcError {
+        match self {
+            ClitcError::MissingInformation { path } => ClitcError::MissingInformation {
+                path: format!("{}.{}", prefix, path),
+            },
+            ClitcError::WrongFormat { path, reason } => ClitcError::WrongFormat {
+                path: format!("{}.{}", prefix, path),
+                reason,
+            },
+            ClitcError::ConflictingIdentifier { path, identifier, other } => ClitcError::ConflictingIdentifier {
+                path: format!("{}.{}", prefix, path),
+                identifier,
+                other: format!("{}.{}", prefix, other),
+            },
+            ClitcError::AmbiguousPrefix { path, prefix: name, identifier, other } => ClitcError::AmbiguousPrefix {
+                path: format!("{}.{}", prefix, path),
+                prefix: name,
+                identifier,
+                other: format!("{}.{}", prefix, other),
+            },
+            other => other,
+        }
     }
-}
-
-#[derive(Clone, Debug)]
-pub struct WrongFormatError;
 
-impl Error for WrongFormatError {
-    fn description(&self) -> &str {
-        "Wrong format for params!"
+    /// Reason of an invalid value without its argument index, other errors are passed back
+    fn value_reason(self) -> std::result::Result<String, ClitcError> {
+        return match self {
+            ClitcError::InvalidValue { subparam, raw, expected, .. } => Ok(format!("expected {} for `{}`, got `{}`", expected, subparam, raw)),
+            ClitcError::ConstraintViolation { subparam, raw, reason, .. } => Ok(format!("`{}` for `{}` {}", raw, subparam, reason)),
+            ClitcError::WrongArity { name, expected, found } => Ok(format!("`{}`: expected {} values, got {}", name, expected, found)),
+            other => Err(other),
+        };
     }
 
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        None
+    /// Describes errors of values read from an environment variable by the variable instead of an argument index
+    pub fn from_env(self, var: &str) -> ClitcError {
+        return match self.value_reason() {
+            Ok(reason) => ClitcError::InvalidEnv { var: var.to_string(), reason },
+            Err(other) => other,
+        };
     }
-}
 
-impl Display for WrongFormatError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "Wrong format for params!")
+    /// Describes errors of values read from a config file by the file and key instead of an argument index
+    pub fn from_config(self, file: &str, key: &str) -> ClitcError {
+        return match self.value_reason() {
+            Ok(reason) => ClitcError::InvalidConfig { file: file.to_string(), key: key.to_string(), reason: format!("has an invalid value: {}", reason) },
+            Err(other) => other,
+        };
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct CommandModeError;
-
-impl Error for CommandModeError {
-    fn description(&self) -> &str {
-        "Multiple commands entered, but single command mode configured!"
-    }
-
+impl Error for ClitcError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
-        None
+        match self {
+            ClitcError::Io(err) => Some(err),
+            ClitcError::Json(err) => Some(err),
+            #[cfg(feature = "toml")]
+            ClitcError::Toml(err) => Some(err),
+            #[cfg(feature = "yaml")]
+            ClitcError::Yaml(err) => Some(err),
+            _ => None,
+        }
     }
 }
 
-impl Display for CommandModeError {
+impl Display for ClitcError {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "Multiple commands entered, but single command mode configured!")
+        match self {
+            ClitcError::Io(err) => write!(f, "Could not read spec file: {}", err),
+            ClitcError::Json(err) => write!(f, "Could not read spec: {}", err),
+            #[cfg(feature = "toml")]
+            ClitcError::Toml(err) => write!(f, "Could not read spec: {}", err),
+            #[cfg(feature = "yaml")]
+            ClitcError::Yaml(err) => write!(f, "Could not read spec: {}", err),
+            ClitcError::MissingInformation { path } => write!(f, "Necessary information missing at `{}`", path),
+            ClitcError::WrongFormat { path, reason } => write!(f, "Wrong format at `{}`: {}", path, reason),
+            ClitcError::DuplicateOrd { option, ord } => write!(f, "{}: ord {} is not unique", option, ord),
+            ClitcError::ConflictingIdentifier { path, identifier, other } => {
+                write!(f, "Identifier `{}` at `{}` is already used at `{}`", identifier, path, other)
+            },
+            ClitcError::AmbiguousPrefix { path, prefix, identifier, other } => {
+                write!(f, "`{}` at `{}` is a prefix of `{}` at `{}`, so it cannot be abbreviated", prefix, path, identifier, other)
+            },
+            ClitcError::AmbiguousOption { token, index, candidates } => {
+                write!(f, "`{}` (arg {}) is ambiguous, it could be {}", token, index, candidates.join(", "))
+            },
+            ClitcError::CommandMode { commands } => write!(f, "Multiple commands entered ({}), but single command mode configured", commands.join(", ")),
+            ClitcError::UnterminatedQuote { quote, position } => write!(f, "Unterminated {} quote starting at position {}", quote, position),
+            ClitcError::NoEvent { command } => write!(f, "No event attached to command `{}`", command),
+            ClitcError::UnknownCommand { tokens } => write!(f, "Could not find a known command in `{}`", tokens.join(" ")),
+            ClitcError::InvalidValue { option, subparam, index, raw, expected } => {
+                write!(f, "{}: expected {} for `{}`, got `{}` (arg {})", option, expected, subparam, raw, index)
+            },
+            ClitcError::ConstraintViolation { option, subparam, index, raw, reason } => {
+                write!(f, "{}: `{}` for `{}` {} (arg {})", option, raw, subparam, reason, index)
+            },
+            ClitcError::UnknownType { name } => write!(f, "No parser registered for type `{}`", name),
+            ClitcError::InvalidEnv { var, reason } => write!(f, "Environment variable `{}`: {}", var, reason),
+            ClitcError::InvalidConfig { file, key, reason } if key.is_empty() => write!(f, "Config `{}`: {}", file, reason),
+            ClitcError::InvalidConfig { file, key, reason } => write!(f, "Config `{}`: `{}` {}", file, key, reason),
+            ClitcError::MissingRequired { missing } => {
+                let items: Vec<String> = missing.iter().map(|(option, subparam)| match subparam {
+                    Some(subparam) => format!("{}: `{}`", option, subparam),
+                    None => option.clone(),
+                }).collect();
+                write!(f, "Missing required arguments: {}", items.join(", "))
+            },
+            ClitcError::WrongArity { name, expected, found } => write!(f, "`{}`: expected {} values, got {}", name, expected, found),
+            ClitcError::HelpRequested { help } => write!(f, "{}", help),
+            ClitcError::TypeMismatch { name, expected, found } => write!(f, "{}: requested as {}, but declared as {}", name, expected, found),
+        }
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct NoEventError;
-
-impl Error for NoEventError {
-    fn description(&self) -> &str {
-        "No event attached to this command!"
-    }
-
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        None
+impl From<std::io::Error> for ClitcError {
+    fn from(err: std::io::Error) -> ClitcError {
+        ClitcError::Io(err)
     }
 }
 
-impl Display for NoEventError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "No event attached to this command!")
+impl From<serde_json::Error> for ClitcError {
+    fn from(err: serde_json::Error) -> ClitcError {
+        ClitcError::Json(err)
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct UnknownCommandError;
-
-impl Error for UnknownCommandError
- {
-    fn description(&self) -> &str {
-        "Could not find a known command in the statement"
-    }
-
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        None
+#[cfg(feature = "toml")]
+impl From<toml::de::Error> for ClitcError {
+    fn from(err: toml::de::Error) -> ClitcError {
+        ClitcError::Toml(err)
     }
 }
 
-impl Display for UnknownCommandError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "Could not find a known command in the statement")
+#[cfg(feature = "yaml")]
+impl From<serde_yaml::Error> for ClitcError {
+    fn from(err: serde_yaml::Error) -> ClitcError {
+        ClitcError::Yaml(err)
     }
 }
@@ -1,7 +1,6 @@
 use std::{
     ops::Fn,
     collections::HashMap,
-    error::Error,
     rc::Rc,
     sync::{Arc, Mutex},
 };
@@ -9,46 +8,126 @@ use std::{
 use super::params::{
     CliParameters,
     ParamValue,
+    ParseResult,
 };
 
-use super::clitc_error::{
-    CommandModeError,
-    NoEventError,
-    UnknownCommandError,
-};
+use super::clitc_error::ClitcError;
 
 type ParamResult = HashMap<String, ParamValue>;
-type CallbackFn<T> = dyn Fn(T, ParamResult) -> ();
-type InfoFn<T> = dyn Fn(T, ParamResult, HashMap<String, Vec<String>>) -> ();
+type CallbackFn<T> = dyn Fn(T, ParamResult);
+type InfoFn<T> = dyn Fn(T, ParamResult, HashMap<String, Vec<String>>);
 type EmitHandle<T> = Arc<Mutex<Option<T>>>;
-type EmitFn<T, E> = dyn Fn(T, EmitHandle<E>, ParamResult) -> ();
+type EmitFn<T, E> = dyn Fn(T, EmitHandle<E>, ParamResult);
+type CommandFn<T> = dyn Fn(T, ParseResult);
 
 pub enum Event<T: Clone, E> {
     Callback(Rc<CallbackFn<T>>),
     InfoCallback(Rc<InfoFn<T>>),
     Emit(EmitHandle<E>, Rc<EmitFn<T, E>>),
+    /// Receives the entire parse result, including the options of a subcommand
+    Command(Rc<CommandFn<T>>),
 }
 
 impl<T: Clone, E> Clone for Event<T, E> {
     fn clone(&self) -> Event<T, E> {
         match self {
-            Event::Callback(f) => Event::Callback(Rc::clone(&f)),
-            Event::InfoCallback(f) => Event::InfoCallback(Rc::clone(&f)),
-            Event::Emit(h, f) => Event::Emit(Arc::clone(&h), Rc::clone(&f)),
+            Event::Callback(f) => Event::Callback(Rc::clone(f)),
+            Event::InfoCallback(f) => Event::InfoCallback(Rc::clone(f)),
+            Event::Emit(h, f) => Event::Emit(Arc::clone(h), Rc::clone(f)),
+            Event::Command(f) => Event::Command(Rc::clone(f)),
         }
     }
 }
 
 pub trait Split {
-    fn split(&self, s: String) -> Vec<String>;
+    fn split(&self, s: String) -> Result<Vec<String>, ClitcError>;
 }
 
 pub struct WhitespaceSplitter;
 
 impl Split for WhitespaceSplitter {
-    fn split(&self, s: String) -> Vec<String> {
-        s.split_whitespace().map(|x| x.to_string()).collect()
+    fn split(&self, s: String) -> Result<Vec<String>, ClitcError> {
+        Ok(s.split_whitespace().map(|x| x.to_string()).collect())
+    }
+}
+
+/// Splits like a POSIX shell: single quotes are literal, double quotes allow escaping `"`, `\`, `$` and `` ` ``,
+/// and a backslash outside of quotes escapes any character. Quoted empty strings are kept as empty tokens.
+pub struct ShellSplitter;
+
+impl Split for ShellSplitter {
+    fn split(&self, s: String) -> Result<Vec<String>, ClitcError> {
+        let mut tokens = Vec::new();
+        let mut token = String::new();
+        // Set once the current token has started, so that `""` gives an empty token
+        let mut in_token = false;
+        let mut chars = s.char_indices().peekable();
+        while let Some((position, c)) = chars.next() {
+            match c {
+                c if c.is_whitespace() => {
+                    if in_token {
+                        tokens.push(std::mem::take(&mut token));
+                        in_token = false;
+                    }
+                },
+                '\'' => {
+                    in_token = true;
+                    loop {
+                        match chars.next() {
+                            Some((_, '\'')) => break,
+                            Some((_, c)) => token.push(c),
+                            None => return Err(ClitcError::UnterminatedQuote { quote: '\'', position }),
+                        }
+                    }
+                },
+                '"' => {
+                    in_token = true;
+                    loop {
+                        match chars.next() {
+                            Some((_, '"')) => break,
+                            Some((_, '\\')) => match chars.peek() {
+                                Some((_, escaped)) if "\"\\$`".contains(*escaped) => {
+                                    token.push(*escaped);
+                                    chars.next();
+                                },
+                                _ => token.push('\\'),
+                            },
+                            Some((_, c)) => token.push(c),
+                            None => return Err(ClitcError::UnterminatedQuote { quote: '"', position }),
+                        }
+                    }
+                },
+                '\\' => {
+                    in_token = true;
+                    // A trailing backslash stays as it is
+                    let (_, escaped) = chars.next().unwrap_or((position, '\\'));
+                    token.push(escaped);
+                },
+                c => {
+                    in_token = true;
+                    token.push(c);
+                },
+            }
+        }
+        if in_token {
+            tokens.push(token);
+        }
+        return Ok(tokens);
+    }
+}
+
+/// Arguments of a subcommand event: its positionals, the values of its options keyed by option and subparam name and its flags
+fn command_args(res: &ParseResult) -> ParamResult {
+    let mut args = res.positionals.clone();
+    for (option, values) in res.options.iter() {
+        if values.is_empty() {
+            args.insert(option.clone(), ParamValue::Bool(true));
+        }
+        for (subparam, value) in values.iter() {
+            args.insert(format!("{} {}", option, subparam), value.clone());
+        }
     }
+    return args;
 }
 
 pub struct EventHandler<S, T, E>
@@ -71,6 +150,10 @@ impl<S: Split, T: Clone, E> EventHandler<S, T, E> {
             context,
         };
         event_handler.cli_params.set_sequential_processing(true);
+        // Commands are entered without the program name
+        if let Some(help) = event_handler.cli_params.help().cloned() {
+            event_handler.cli_params.set_help(Some(help.program("")));
+        }
         return event_handler;
     }
 
@@ -79,9 +162,12 @@ impl<S: Split, T: Clone, E> EventHandler<S, T, E> {
         for param in self.cli_params.iter() {
             text.insert(param.name.clone(), param.info());
         }
+        text.extend(self.cli_params.subcommand_info());
         return text;
     }
 
+    /// Events are keyed by option name, or by command path (e.g. `"db migrate"`) for subcommands.
+    /// Events of subcommands get their positionals and the values of their options keyed like `"--count n"`, flags like `"--dry-run"`.
     pub fn attach(&mut self, events: HashMap<String, Event<T, E>>) {
         self.events = events;
     }
@@ -92,7 +178,7 @@ impl<S: Split, T: Clone, E> EventHandler<S, T, E> {
         return ret;
     }
 
-    fn invoke_event(&self, key: String, args: HashMap<String, ParamValue>) -> Result<(), NoEventError> {
+    fn invoke_event(&self, key: String, args: HashMap<String, ParamValue>, res: &ParseResult) -> Result<(), ClitcError> {
         if let Some(evt) = self.events.get(&key) {
             // Callback function called if connected event can be found
             match evt {
@@ -100,32 +186,96 @@ impl<S: Split, T: Clone, E> EventHandler<S, T, E> {
                 // Return with  entire cmd info if requested (help cmds)
                 Event::InfoCallback(callback) => callback(self.context.clone(), args, self.get_info()),
                 // Return emit handle
-                Event::Emit(handle, callback) => callback(self.context.clone(), Arc::clone(&handle), args),
+                Event::Emit(handle, callback) => callback(self.context.clone(), Arc::clone(handle), args),
+                Event::Command(callback) => callback(self.context.clone(), res.clone()),
             };
         } else {
             // No Events with this identifier found
-            return Err(NoEventError);
+            return Err(ClitcError::NoEvent { command: key });
         }
         return Ok(());
     }
 
-    pub fn pass_command(&self, data: String) -> Result<(), Box<dyn Error>> {
-        let res = self.cli_params.parse_str(&data[..], |x| self.split_fn.split(x.to_string()));
+    /// Invokes the events of a command.
+    /// With the built-in help enabled, `help`, `help <cmd>`, `--help` and `-h` without an attached event return the rendered help as `ClitcError::HelpRequested`.
+    pub fn pass_command(&self, data: String) -> Result<(), ClitcError> {
+        let tokens = self.split_fn.split(data)?;
+        // A help command without an event is answered by the built-in help, the following tokens are its topic
+        if let (Some(help), Some(first)) = (self.cli_params.help(), tokens.first()) {
+            if first.trim_start_matches('-') == "help" && !self.events.keys().any(|x| x.trim_start_matches('-') == "help") {
+                let topic = tokens[1..].to_vec();
+                return match help.render(&self.cli_params, &topic) {
+                    Some(help) => Err(ClitcError::HelpRequested { help }),
+                    None => Err(ClitcError::UnknownCommand { tokens: topic }),
+                };
+            }
+        }
+        let res = self.cli_params.parse_vec(tokens.clone())?;
+        // Subcommands are dispatched as a whole
+        if !res.command.is_empty() {
+            return self.invoke_event(res.command.join(" "), command_args(&res), &res);
+        }
         // Check if there were any known commands found
-        if res.is_empty() {
+        if res.options.is_empty() {
             // Could not identify any known command
-            return Err(Box::new(UnknownCommandError));
+            return Err(ClitcError::UnknownCommand { tokens });
         }
         // Check if command count and single command mode don't collide
-        if res.len() > 1 && self.single_cmd {
+        if res.options.len() > 1 && self.single_cmd {
             // if multiple commands were entered, single command mode was infringed
-            return Err(Box::new(CommandModeError));
+            return Err(ClitcError::CommandMode { commands: res.options.into_keys().collect() });
         }
         // Find connected events for parsed commands
-        for (cmd, args) in res.into_iter() {
+        for (cmd, args) in res.options.clone().into_iter() {
             // Abort if invoking throws Error
-            self.invoke_event(cmd, args)?;
+            self.invoke_event(cmd, args, &res)?;
         }
         return Ok(());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(s: &str) -> Result<Vec<String>, ClitcError> {
+        return Split::split(&ShellSplitter, s.to_string());
+    }
+
+    fn tokens(list: &[&str]) -> Vec<String> {
+        return list.iter().map(|x| x.to_string()).collect();
+    }
+
+    #[test]
+    fn shell_quotes() {
+        assert_eq!(split("say \"hello world\"  'a b'").unwrap(), tokens(&["say", "hello world", "a b"]));
+        assert_eq!(split("a\"b\"'c'd").unwrap(), tokens(&["abcd"]));
+        assert_eq!(split("'\"' \"'\"").unwrap(), tokens(&["\"", "'"]));
+    }
+
+    #[test]
+    fn shell_empty_strings() {
+        assert_eq!(split("set \"\" ''").unwrap(), tokens(&["set", "", ""]));
+        assert_eq!(split("   ").unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn shell_escapes() {
+        assert_eq!(split(r"a\ b c\\d").unwrap(), tokens(&["a b", r"c\d"]));
+        assert_eq!(split(r#""a\"b\$c\n""#).unwrap(), tokens(&[r#"a"b$c\n"#]));
+        assert_eq!(split(r"'a\'").unwrap(), tokens(&[r"a\"]));
+        assert_eq!(split(r"end\").unwrap(), tokens(&[r"end\"]));
+    }
+
+    #[test]
+    fn shell_unterminated_quotes() {
+        match split("say 'hello") {
+            Err(ClitcError::UnterminatedQuote { quote, position }) => assert_eq!((quote, position), ('\'', 4)),
+            res => panic!("{:?}", res),
+        }
+        match split("a \"b\" \"c") {
+            Err(ClitcError::UnterminatedQuote { quote, position }) => assert_eq!((quote, position), ('"', 6)),
+            res => panic!("{:?}", res),
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use super::params::{
+    CliParameters,
+    Command,
+    Multiple,
+    Param,
+    SubParam,
+};
+
+/// Renders usage synopses and help pages of a spec, wrapped to a maximum width
+#[derive(Clone)]
+pub struct HelpFormatter {
+    program: String,
+    width: usize,
+    /// Widest left column, longer entries start their text on the next line
+    max_column: usize,
+}
+
+/// Label in the left column and lines of text in the right column of a help page
+type Row = (String, Vec<String>);
+
+impl HelpFormatter {
+    /// Formatter for the given program name, as wide as the terminal according to `COLUMNS`, or 80 columns
+    pub fn new(program: &str) -> HelpFormatter {
+        let width = std::env::var("COLUMNS").ok().and_then(|x| x.parse().ok()).unwrap_or(80);
+        return HelpFormatter {
+            program: program.to_string(),
+            width,
+            max_column: 30,
+        };
+    }
+
+    pub fn program(mut self, program: &str) -> HelpFormatter {
+        self.program = program.to_string();
+        return self;
+    }
+
+    pub fn width(mut self, width: usize) -> HelpFormatter {
+        self.width = width;
+        return self;
+    }
+
+    /// Usage synopsis of the command at `path`, e.g. `prog [-v] [-l <secs> <expected_val>] <input>`
+    pub fn usage(&self, params: &CliParameters, path: &[String]) -> Option<String> {
+        let command = find_command(params.root(), path)?;
+        return Some(self.command_synopsis(command, path));
+    }
+
+    /// Help page of the command at `path`, or of an option if the path ends with its name, e.g. `["serve", "--port"]`
+    pub fn render(&self, params: &CliParameters, path: &[String]) -> Option<String> {
+        if let Some(command) = find_command(params.root(), path) {
+            return Some(self.command_page(command, path));
+        }
+        let (option, path) = path.split_last()?;
+        let command = find_command(params.root(), path)?;
+        let param = command.iter().find(|x| {
+            x.name == *option || x.short.as_deref() == Some(option) || x.name.trim_start_matches('-') == option
+        })?;
+        let rows = [option_row(param)];
+        let mut lines = vec![self.synopsis("Usage:", path, vec![option_items(param)]), String::new()];
+        lines.append(&mut self.rows(&rows, self.column(&rows)));
+        return Some(lines.join("\n"));
+    }
+
+    fn command_page(&self, command: &Command, path: &[String]) -> String {
+        let mut lines = vec![self.command_synopsis(command, path)];
+        if let Some(descr) = &command.descr {
+            lines.push(String::new());
+            lines.extend(descr.lines().flat_map(|x| wrap(x, self.width)));
+        }
+
+        // Options without a group come first, groups follow in order of appearance
+        let mut sections: Vec<(String, Vec<Row>)> = vec![(String::from("Options"), Vec::new())];
+        for param in command.iter() {
+            let group = param.group.clone().unwrap_or_else(|| String::from("Options"));
+            match sections.iter_mut().find(|x| x.0 == group) {
+                Some(section) => section.1.push(option_row(param)),
+                None => sections.push((group, vec![option_row(param)])),
+            }
+        }
+        let positionals = command.positionals().map(|x| (positional_synopsis(&x.param), vec![subparam_details(&x.param)])).collect();
+        sections.push((String::from("Arguments"), positionals));
+        let subcommands = command.subcommands().map(|x| (x.name.clone(), x.descr.iter().flat_map(|x| x.lines()).map(|x| x.to_string()).collect())).collect();
+        sections.push((String::from("Commands"), subcommands));
+        sections.retain(|x| !x.1.is_empty());
+
+        // Columns are aligned across all sections
+        let all: Vec<Row> = sections.iter().flat_map(|x| x.1.iter().cloned()).collect();
+        let column = self.column(&all);
+        for (title, rows) in sections.iter() {
+            lines.push(String::new());
+            lines.push(format!("{}:", title));
+            lines.append(&mut self.rows(rows, column));
+        }
+        return lines.join("\n");
+    }
+
+    /// Synopsis of the options and positionals of a command.
+    /// Subcommands get a line of their own, as they have to come before any positional.
+    fn command_synopsis(&self, command: &Command, path: &[String]) -> String {
+        let mut lines = vec![self.synopsis("Usage:", path, command_items(command))];
+        if command.subcommands().next().is_some() {
+            lines.push(self.synopsis("      ", path, vec![String::from("<command> ...")]));
+        }
+        return lines.join("\n");
+    }
+
+    /// Line starting with `label`, the program and command path, continued lines are indented behind it
+    fn synopsis(&self, label: &str, path: &[String], items: Vec<String>) -> String {
+        let mut prefix: Vec<&str> = vec![label];
+        if !self.program.is_empty() {
+            prefix.push(&self.program);
+        }
+        prefix.extend(path.iter().map(|x| x.as_str()));
+        let prefix = prefix.join(" ");
+        let indent = " ".repeat(prefix.len() + 1);
+        let mut lines = vec![prefix];
+        for item in items.into_iter() {
+            let last = lines.last_mut().unwrap();
+            if last.len() + 1 + item.len() > self.width && last.len() > indent.len() {
+                lines.push(format!("{}{}", indent, item));
+            } else {
+                last.push(' ');
+                last.push_str(&item);
+            }
+        }
+        return lines.join("\n");
+    }
+
+    /// Start of the right column, fitting the labels up to `max_column`
+    fn column(&self, rows: &[Row]) -> usize {
+        let widest = rows.iter().map(|x| x.0.len()).filter(|x| *x <= self.max_column).max().unwrap_or(0);
+        return widest + 4;
+    }
+
+    fn rows(&self, rows: &[Row], column: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        let text_width = self.width.saturating_sub(column).max(20);
+        for (label, text) in rows.iter() {
+            let mut text: Vec<String> = text.iter().flat_map(|x| wrap(x, text_width)).collect();
+            let label = format!("  {}", label);
+            if label.len() + 2 > column || text.is_empty() {
+                lines.push(label);
+            } else {
+                let first = text.remove(0);
+                lines.push(format!("{:width$}{}", label, first, width = column));
+            }
+            lines.extend(text.into_iter().map(|x| format!("{}{}", " ".repeat(column), x)));
+        }
+        return lines;
+    }
+}
+
+impl Default for HelpFormatter {
+    fn default() -> HelpFormatter {
+        return HelpFormatter::new("");
+    }
+}
+
+/// Synopsis items of the options and positionals of a command
+fn command_items(command: &Command) -> Vec<String> {
+    let mut items: Vec<String> = command.iter().map(option_synopsis).collect();
+    items.extend(command.positionals().map(|x| positional_synopsis(&x.param)));
+    return items;
+}
+
+fn find_command<'a>(command: &'a Command, path: &[String]) -> Option<&'a Command> {
+    let mut command = command;
+    for name in path.iter() {
+        command = command.subcommands().find(|x| x.name == *name)?;
+    }
+    return Some(command);
+}
+
+/// Placeholder of a subparam, e.g. `<secs>`, `<files>...` for arrays and `<tags,...>` for arrays with a delimiter
+fn placeholder(subparam: &SubParam) -> String {
+    let name = subparam.name.clone().unwrap_or_else(|| subparam.ptype.info().to_string());
+    if subparam.delimiter.is_some() {
+        return format!("<{},...>", name);
+    }
+    match subparam.get_arity().max {
+        Some(1) => format!("<{}>", name),
+        _ => format!("<{}>...", name),
+    }
+}
+
+/// Identifier of an option with the placeholders of its subparams, e.g. `-l <secs> <expected_val>`
+fn option_items(param: &Param) -> String {
+    let mut items = vec![param.short.clone().unwrap_or_else(|| param.name.clone())];
+    items.extend(param.params.iter().map(placeholder));
+    return items.join(" ");
+}
+
+fn option_synopsis(param: &Param) -> String {
+    let repeat = if param.multiple == Multiple::Last { "" } else { "..." };
+    if param.required {
+        return format!("{}{}", option_items(param), repeat);
+    }
+    return format!("[{}]{}", option_items(param), repeat);
+}
+
+fn positional_synopsis(subparam: &SubParam) -> String {
+    if subparam.required {
+        return placeholder(subparam);
+    }
+    return format!("[{}]", placeholder(subparam));
+}
+
+/// Type, default, required and env of a subparam, e.g. `int [default: 60] [env: APP_SECS]`
+fn subparam_details(subparam: &SubParam) -> String {
+    let mut details = subparam.type_info();
+    if subparam.required {
+        details.push_str(" [required]");
+    }
+    if let Some(default) = &subparam.default {
+        details.push_str(&format!(" [default: {}]", default));
+    }
+    if let Some(var) = &subparam.env {
+        details.push_str(&format!(" [env: {}]", var));
+    }
+    return details;
+}
+
+fn option_row(param: &Param) -> Row {
+    // Long names line up whether or not there is a short identifier
+    let mut label = match &param.short {
+        Some(short) => format!("{}, {}", short, param.name),
+        None if param.name.starts_with("--") => format!("    {}", param.name),
+        None => param.name.clone(),
+    };
+    for subparam in param.params.iter() {
+        label.push(' ');
+        label.push_str(&placeholder(subparam));
+    }
+
+    let mut text: Vec<String> = param.descr.iter().flat_map(|x| x.lines()).map(|x| x.to_string()).collect();
+    let mut tags = Vec::new();
+    if param.required {
+        tags.push(String::from("[required]"));
+    }
+    if param.multiple != Multiple::Last {
+        tags.push(String::from("[repeatable]"));
+    }
+    if let Some(var) = &param.env {
+        tags.push(format!("[env: {}]", var));
+    }
+    if !tags.is_empty() {
+        text.push(tags.join(" "));
+    }
+    for subparam in param.params.iter() {
+        // Unnamed subparams are already shown by their type
+        let details = subparam_details(subparam);
+        if subparam.name.is_some() || details != subparam.type_info() {
+            text.push(format!("{}: {}", placeholder(subparam), details));
+        }
+    }
+    return (label, text);
+}
+
+/// Wraps a line at whitespace, words longer than `width` get a line of their own
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in line.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    // Empty lines of a description are kept as paragraph breaks
+    lines.push(current);
+    return lines;
+}
@@ -1,19 +1,17 @@
+#![allow(clippy::needless_return)]
+
+extern crate regex;
 extern crate serde_json;
+#[cfg(feature = "yaml")]
+extern crate serde_yaml;
+#[cfg(feature = "toml")]
+extern crate toml;
 
 pub mod clitc_error;
 pub mod events;
+pub mod help;
+pub mod lint;
 pub mod params;
 
-use std::{
-    time::Instant,
-    fs::File,
-};
-use crate::params::{
-    ParamValue,
-    CliParameters,
-};
-use crate::events::{
-    WhitespaceSplitter,
-    EventHandler,
-    Event,
-};
+#[cfg(feature = "derive")]
+pub use clitc_derive::Clitc;
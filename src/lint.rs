@@ -0,0 +1,376 @@
+extern crate serde_json;
+
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter, Result},
+};
+use serde_json::Value;
+use super::clitc_error::ClitcError;
+use super::params::CliParameters;
+
+/// JSON Schema describing the spec format read by `CliParameters::from_str`
+pub const SPEC_SCHEMA: &str = include_str!("../schema/clitc-spec.schema.json");
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    /// JSON pointer to the offending value, e.g. `/options/1/short`
+    pub pointer: String,
+    /// Line of the offending value in the spec file, starting at 1
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{} (at {})", self.message, self.pointer)
+        }
+    }
+}
+
+fn problem(problems: &mut Vec<Problem>, pointer: &str, message: String) {
+    problems.push(Problem { pointer: pointer.to_string(), line: None, message });
+}
+
+/// Checks a JSON spec against `SPEC_SCHEMA` and semantic rules, returning every problem found.
+/// `custom_types` are the names of types with a `ValueParser`, which are accepted besides the built-in ones.
+pub fn lint_str(data: &str, custom_types: &[String]) -> Vec<Problem> {
+    let val: Value = match serde_json::from_str(data) {
+        Ok(val) => val,
+        Err(err) => return vec![Problem { pointer: String::new(), line: Some(err.line()), message: err.to_string() }],
+    };
+
+    let mut problems = Vec::new();
+    let mut schema: Value = serde_json::from_str(SPEC_SCHEMA).expect("Spec schema is not valid JSON");
+    if let Some(types) = schema["definitions"]["type"]["enum"].as_array_mut() {
+        types.extend(custom_types.iter().map(|x| Value::from(x.clone())));
+    }
+    check_schema(&schema, &schema, &val, "", &mut problems);
+    check_command(&val, "", &mut problems);
+
+    // Remaining checks of the loader, e.g. types of default values
+    if problems.is_empty() {
+        if let Err(err) = CliParameters::from_str(data) {
+            let pointer = match &err {
+                ClitcError::MissingInformation { path }
+                | ClitcError::WrongFormat { path, .. }
+                | ClitcError::ConflictingIdentifier { path, .. }
+                | ClitcError::AmbiguousPrefix { path, .. } => to_pointer(path),
+                _ => String::new(),
+            };
+            problem(&mut problems, &pointer, err.to_string());
+        }
+    }
+
+    let lines = locate(data);
+    for problem in problems.iter_mut() {
+        // Fall back to the closest enclosing value which could be located
+        let mut pointer = &problem.pointer[..];
+        while problem.line.is_none() {
+            problem.line = lines.get(pointer).cloned();
+            match pointer.rfind('/') {
+                Some(i) => pointer = &pointer[..i],
+                None => break,
+            }
+        }
+    }
+    return problems;
+}
+
+/// Converts an error path like `options[0].params[1].default` to a JSON pointer
+fn to_pointer(path: &str) -> String {
+    let mut pointer = String::new();
+    for part in path.split('.') {
+        for segment in part.split(['[', ']']).filter(|x| !x.is_empty()) {
+            pointer.push('/');
+            pointer.push_str(segment);
+        }
+    }
+    return pointer;
+}
+
+fn type_of(val: &Value) -> &'static str {
+    match val {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn matches_type(val: &Value, expected: &str) -> bool {
+    let found = type_of(val);
+    return found == expected || (expected == "number" && found == "integer");
+}
+
+/// Validates `val` against the subset of JSON Schema used by `SPEC_SCHEMA`
+fn check_schema(root: &Value, schema: &Value, val: &Value, pointer: &str, problems: &mut Vec<Problem>) {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference.trim_start_matches("#/definitions/");
+        check_schema(root, &root["definitions"][name], val, pointer, problems);
+        return;
+    }
+
+    let types: Vec<&str> = match &schema["type"] {
+        Value::String(t) => vec![t],
+        Value::Array(ts) => ts.iter().filter_map(|x| x.as_str()).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|t| matches_type(val, t)) {
+        problem(problems, pointer, format!("expected {}, found {}", types.join(" or "), type_of(val)));
+        return;
+    }
+
+    if let Some(allowed) = schema["enum"].as_array() {
+        if !allowed.contains(val) {
+            let allowed: Vec<String> = allowed.iter().map(|x| x.to_string()).collect();
+            problem(problems, pointer, format!("{} is not one of {}", val, allowed.join(", ")));
+        }
+    }
+
+    if let (Some(min), Some(n)) = (schema["minimum"].as_f64(), val.as_f64()) {
+        if n < min {
+            problem(problems, pointer, format!("{} is lower than the minimum {}", n, min));
+        }
+    }
+    if let (Some(max), Some(n)) = (schema["maximum"].as_f64(), val.as_f64()) {
+        if n > max {
+            problem(problems, pointer, format!("{} is greater than the maximum {}", n, max));
+        }
+    }
+
+    if let Some(alternatives) = schema["anyOf"].as_array() {
+        let matched = alternatives.iter().any(|alternative| {
+            let mut sub_problems = Vec::new();
+            check_schema(root, alternative, val, pointer, &mut sub_problems);
+            sub_problems.is_empty()
+        });
+        if !matched {
+            problem(problems, pointer, format!("{} does not match any allowed form", val));
+        }
+    }
+
+    if let Some(obj) = val.as_object() {
+        if let Some(required) = schema["required"].as_array() {
+            for key in required.iter().filter_map(|x| x.as_str()) {
+                if !obj.contains_key(key) {
+                    problem(problems, pointer, format!("missing required field `{}`", key));
+                }
+            }
+        }
+        for (key, member) in obj.iter() {
+            let member_pointer = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+            match schema["properties"].get(key) {
+                Some(member_schema) => check_schema(root, member_schema, member, &member_pointer, problems),
+                None => {
+                    if schema["additionalProperties"] == Value::Bool(false) {
+                        problem(problems, &member_pointer, format!("unknown field `{}`", key));
+                    }
+                },
+            }
+        }
+    }
+
+    if let Some(vec) = val.as_array() {
+        if let Some(min) = schema["minItems"].as_u64() {
+            if (vec.len() as u64) < min {
+                problem(problems, pointer, format!("expected at least {} items, found {}", min, vec.len()));
+            }
+        }
+        if let Some(max) = schema["maxItems"].as_u64() {
+            if (vec.len() as u64) > max {
+                problem(problems, pointer, format!("expected at most {} items, found {}", max, vec.len()));
+            }
+        }
+        if schema.get("items").is_some() {
+            for (i, item) in vec.iter().enumerate() {
+                check_schema(root, &schema["items"], item, &format!("{}/{}", pointer, i), problems);
+            }
+        }
+    }
+}
+
+/// Semantic rules for a command and its subcommands
+fn check_command(val: &Value, pointer: &str, problems: &mut Vec<Problem>) {
+    // Identifiers must be unique across the shorts and names of all options
+    let mut identifiers: HashMap<&str, String> = HashMap::new();
+    for (i, option) in val["options"].as_array().unwrap_or(&Vec::new()).iter().enumerate() {
+        for key in ["short", "name"].iter() {
+            if let Some(identifier) = option[*key].as_str() {
+                let option_pointer = format!("{}/options/{}/{}", pointer, i, key);
+                if let Some(other) = identifiers.get(identifier) {
+                    problem(problems, &option_pointer, format!("identifier `{}` is already used at {}", identifier, other));
+                } else {
+                    identifiers.insert(identifier, option_pointer);
+                }
+            }
+        }
+        let name = option["name"].as_str().unwrap_or("option");
+        check_subparams(&option["params"], &format!("{}/options/{}/params", pointer, i), name, problems);
+    }
+
+    check_subparams(&val["positionals"], &format!("{}/positionals", pointer), "positionals", problems);
+
+    let mut names: HashMap<&str, String> = HashMap::new();
+    for (i, subcommand) in val["subcommands"].as_array().unwrap_or(&Vec::new()).iter().enumerate() {
+        let subcommand_pointer = format!("{}/subcommands/{}", pointer, i);
+        if let Some(name) = subcommand["name"].as_str() {
+            if let Some(other) = names.get(name) {
+                problem(problems, &format!("{}/name", subcommand_pointer), format!("subcommand `{}` is already defined at {}", name, other));
+            } else {
+                names.insert(name, subcommand_pointer.clone());
+            }
+        }
+        check_command(subcommand, &subcommand_pointer, problems);
+    }
+}
+
+/// Ords must be unique and arrays, which take all remaining tokens, must come last
+fn check_subparams(val: &Value, pointer: &str, owner: &str, problems: &mut Vec<Problem>) {
+    let subparams = match val.as_array() {
+        Some(subparams) => subparams,
+        None => return,
+    };
+
+    let mut ords: HashMap<u64, usize> = HashMap::new();
+    for (i, subparam) in subparams.iter().enumerate() {
+        if let Some(ord) = subparam["ord"].as_u64() {
+            if let Some(other) = ords.get(&ord) {
+                problem(problems, &format!("{}/{}/ord", pointer, i), format!("{}: ord {} is already used at {}/{}", owner, ord, pointer, other));
+            } else {
+                ords.insert(ord, i);
+            }
+        }
+    }
+
+    let last_ord = ords.keys().max().cloned();
+    for (i, subparam) in subparams.iter().enumerate() {
+        // Arrays with a delimiter take a single token by default
+        let bounded = subparam["arity"].as_u64().is_some()
+            || subparam["arity"][1].as_u64().is_some()
+            || (subparam["delimiter"].is_string() && subparam.get("arity").is_none());
+        if subparam["type"] == "array" && !bounded && subparam["ord"].as_u64() != last_ord {
+            let name = subparam["name"].as_str().map(|x| x.to_string()).unwrap_or_else(|| subparam["ord"].to_string());
+            problem(problems, &format!("{}/{}", pointer, i), format!("{}: array `{}` is not last, so the following values can never be filled", owner, name));
+        }
+    }
+}
+
+/// Line of every value in a JSON document, keyed by JSON pointer.
+/// Members of objects are located at their key.
+fn locate(data: &str) -> HashMap<String, usize> {
+    let mut scanner = Scanner { bytes: data.as_bytes(), pos: 0, line: 1, lines: HashMap::new() };
+    scanner.value(String::new());
+    return scanner.lines;
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,
+    lines: HashMap<String, usize>,
+}
+
+impl<'a> Scanner<'a> {
+    fn peek(&self) -> Option<u8> {
+        return self.bytes.get(self.pos).cloned();
+    }
+
+    fn bump(&mut self) {
+        if self.peek() == Some(b'\n') {
+            self.line += 1;
+        }
+        self.pos += 1;
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\r') | Some(b'\n') = self.peek() {
+            self.bump();
+        }
+    }
+
+    fn string(&mut self) -> String {
+        let mut bytes = Vec::new();
+        self.bump();
+        while let Some(c) = self.peek() {
+            self.bump();
+            match c {
+                b'"' => break,
+                b'\\' => {
+                    if let Some(escaped) = self.peek() {
+                        bytes.push(escaped);
+                        self.bump();
+                    }
+                },
+                c => bytes.push(c),
+            }
+        }
+        return String::from_utf8_lossy(&bytes).into_owned();
+    }
+
+    fn value(&mut self, pointer: String) {
+        self.skip_whitespace();
+        self.lines.entry(pointer.clone()).or_insert(self.line);
+        match self.peek() {
+            Some(b'{') => {
+                self.bump();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b'"') => {
+                            let key = self.string().replace('~', "~0").replace('/', "~1");
+                            let member = format!("{}/{}", pointer, key);
+                            self.lines.insert(member.clone(), self.line);
+                            self.skip_whitespace();
+                            // Skip colon
+                            self.bump();
+                            self.value(member);
+                        },
+                        Some(b',') => self.bump(),
+                        Some(b'}') => {
+                            self.bump();
+                            break;
+                        },
+                        _ => break,
+                    }
+                }
+            },
+            Some(b'[') => {
+                self.bump();
+                let mut index = 0;
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.bump(),
+                        Some(b']') => {
+                            self.bump();
+                            break;
+                        },
+                        None => break,
+                        _ => {
+                            self.value(format!("{}/{}", pointer, index));
+                            index += 1;
+                        },
+                    }
+                }
+            },
+            Some(b'"') => {
+                self.string();
+            },
+            _ => {
+                // Numbers and literals
+                while let Some(c) = self.peek() {
+                    if c == b',' || c == b']' || c == b'}' || c.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.bump();
+                }
+            },
+        }
+    }
+}
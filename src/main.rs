@@ -1,20 +1,19 @@
-extern crate serde_json;
+#![allow(clippy::needless_return)]
 
-mod clitc_error;
-mod events;
-mod params;
+extern crate clitc;
 
 use std::{
     time::Instant,
     fs::File,
     collections::HashMap,
+    convert::TryFrom,
     rc::Rc,
 };
-use crate::params::{
-    ParamValue,
+use clitc::params::{
     CliParameters,
 };
-use crate::events::{
+use clitc::clitc_error::ClitcError;
+use clitc::events::{
     WhitespaceSplitter,
     EventHandler,
     Event,
@@ -23,82 +22,46 @@ use crate::events::{
 fn main() {
     let total = Instant::now();
     println!("CLI Toolchain");
-    let data = r#"
-    {
-        "options": [
-            {
-                "short": "-e",
-                "name": "--example",
-                "descr": "Lorem ipsum\nLorem Ipsum dolor est",
-                "params": [
-                    {
-                        "ord": 0,
-                        "type": "string"
-                    }
-                ]
-            },
-            {
-                "short": "-v",
-                "name": "--verbose",
-                "descr": "Lorem ipsum\nLorem Ipsum dolor est",
-                "params": []
-            },
-            {
-                "short": "-l",
-                "name": "--lifetime",
-                "descr": "Lorem ipsum\nLorem Ipsum dolor est",
-                "params": [
-                    {
-                        "ord": 0,
-                        "name": "secs",
-                        "type": "int"
-                    },
-                    {
-                        "ord": 1,
-                        "name": "expected_val",
-                        "type": "num"
-                    }
-                ]
-            }
-        ]
-    }"#;
+    let data = include_str!("demo_spec.json");
 
     let timer = Instant::now();
-    let cli_params = CliParameters::from_str(data).expect("Error occurred");
+    let mut cli_params = CliParameters::from_str(data).expect("Error occurred");
+    cli_params.enable_help();
     let elapsed = timer.elapsed();
     println!("File loading: {}µs", elapsed.as_micros());
 
     let timer = Instant::now();
-    let args = cli_params.parse_args();
+    let args = match cli_params.parse_args() {
+        Err(ClitcError::HelpRequested { help }) => {
+            println!("{}", help);
+            return;
+        },
+        args => args.expect("Could not parse cl args"),
+    };
     println!("Parsed:");
-    for (key, val) in args.iter() {
+    for (key, val) in args.options.iter() {
         println!("{}", key);
         for (k, v) in val {
-            println!("\t{}\t{}", k, match v {
-                ParamValue::Array(val) => val.join(", "),
-                ParamValue::Int(val) => val.to_string(),
-                ParamValue::Num(val) => val.to_string(),
-                ParamValue::String(val) => val.clone(),
-            });
+            println!("\t{}\t{}", k, v);
         }
     }
+    for (key, val) in args.positionals.iter() {
+        println!("{}\t{}", key, val);
+    }
     let elapsed = timer.elapsed();
     println!("Parsing cl args: {}µs", elapsed.as_micros());
 
     let timer = Instant::now();
-    let args = cli_params.parse_str_whitespace("-l 2 2.345 --example MyName");
+    let args = cli_params.parse_str_whitespace("-l 2 2.345 --example MyName").expect("Could not parse command");
     println!("Parsed:");
-    for (key, val) in args.iter() {
+    for (key, val) in args.options.iter() {
         println!("{}", key);
         for (k, v) in val {
-            println!("\t{}\t{}", k, match v {
-                ParamValue::Array(val) => val.join(", "),
-                ParamValue::Int(val) => val.to_string(),
-                ParamValue::Num(val) => val.to_string(),
-                ParamValue::String(val) => val.clone(),
-            });
+            println!("\t{}\t{}", k, v);
         }
     }
+    let secs: Option<i64> = args.get("lifetime", "secs").expect("Wrong type for secs");
+    println!("Lifetime: {:?}s", secs);
     let elapsed = timer.elapsed();
     println!("Parsing command: {}µs", elapsed.as_micros());
 
@@ -107,7 +70,8 @@ fn main() {
 
     println!("\n\n## EventHandler ##\n");
     let config_file = File::open("D:/Dateien/tobias/data/clitc/commands.json").expect("Could not open file");
-    let cli_params = CliParameters::from_reader(config_file).expect("Could not parse params");
+    let mut cli_params = CliParameters::from_reader(config_file).expect("Could not parse params");
+    cli_params.enable_help();
     let mut evt_handler = EventHandler::new(cli_params, WhitespaceSplitter, true, ());
 
     let mut events: HashMap<String, Event<(), ()>> = HashMap::new();
@@ -123,43 +87,23 @@ fn main() {
 
 
     events.insert(String::from("show"), Event::Callback(Rc::new(|_, args| {
-        match args.get(&String::from("index")) {
-            Some(val) => println!("Showing value at index {}...", match val {
-                ParamValue::Int(val) => val.to_string(),
-                _ => String::from("Wrong value type!"),
-            }),
+        match args.get("index").map(i64::try_from) {
+            Some(Ok(val)) => println!("Showing value at index {}...", val),
+            Some(Err(err)) => println!("[show] {}", err),
             None => println!("[show] Command needs an index parameter!"),
         };
     })));
 
-    events.insert(String::from("help"), Event::InfoCallback(Rc::new(|_, args, mut info| {
-        match args.get(&String::from("cmd")) {
-            Some(val) => {
-                let cmd = val.to_string();
-                // Print only for single command
-                let lines = match info.get(&cmd) {
-                    Some(ln) => ln.clone(),
-                    None => vec![format!("Cannot display help for unknown command \"{}\"", cmd)],
-                };
-                println!("{}", lines.join("\n"));
-            },
-            None => {
-                // Print for all commands
-                let mut lines = Vec::new();
-                for mut cmd in info.values_mut() {
-                    lines.append(&mut cmd);
-                }
-                println!("{}", lines.join("\n"));
-            },
-        }
-    })));
+    // `help` and `help <cmd>` are answered by the built-in help
 
     evt_handler.attach(events);
     let events = evt_handler.disattach();
+    evt_handler.attach(events);
 
-    evt_handler.pass_command("start".to_string()).expect("Could not pass command");
-    evt_handler.pass_command("help".to_string()).expect("Could not pass command");
-    evt_handler.pass_command("show 1".to_string()).expect("Could not pass command");
-    println!("##");
-    evt_handler.pass_command("help show".to_string()).expect("Could not pass command");
+    for command in ["start", "help", "show 1", "help show"] {
+        match evt_handler.pass_command(command.to_string()) {
+            Err(ClitcError::HelpRequested { help }) => println!("{}", help),
+            res => res.expect("Could not pass command"),
+        }
+    }
 }
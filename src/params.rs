@@ -1,342 +1,2758 @@
+extern crate serde;
 extern crate serde_json;
 
 use std::{
-    cmp::{Ord},
-    error::Error,
+    any::Any,
+    borrow::Cow,
     collections::{HashMap, HashSet},
+    convert::TryFrom,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
-use serde_json::{Value};
-use super::clitc_error::{
-    MissingInformationError,
-    WrongFormatError,
-};
+use regex::Regex;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value};
+use super::clitc_error::ClitcError;
+use super::help::HelpFormatter;
+
+fn missing(path: &str) -> ClitcError {
+    return ClitcError::MissingInformation { path: path.to_string() };
+}
+
+fn is_false(val: &bool) -> bool {
+    return !val;
+}
+
+/// Checks that an upper bound of the spec is not lower than its lower bound
+fn check_bounds<T: PartialOrd + Display>(min: Option<T>, max: Option<T>, key: &str) -> Result<(), ClitcError> {
+    if let (Some(min), Some(max)) = (min, max) {
+        if max < min {
+            return Err(ClitcError::WrongFormat {
+                path: key.to_string(),
+                reason: format!("{} is lower than the minimum {}", max, min),
+            });
+        }
+    }
+    return Ok(());
+}
+
+fn serialize_duration<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    return serializer.serialize_str(&format_duration(duration));
+}
+
+/// Parses durations like `90`, `5s`, `2m`, `1h30m` or `250ms`; plain numbers are seconds
+fn parse_duration(raw: &str) -> Option<Duration> {
+    if let Ok(secs) = raw.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let mut total = Duration::from_secs(0);
+    let mut rest = raw;
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        // Every number needs a unit, except for a single plain number
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let n: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_len = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let secs = match &rest[..unit_len] {
+            "ms" => {
+                total = total.checked_add(Duration::from_millis(n))?;
+                rest = &rest[unit_len..];
+                continue;
+            },
+            "s" => n,
+            "m" => n.checked_mul(60)?,
+            "h" => n.checked_mul(60 * 60)?,
+            "d" => n.checked_mul(24 * 60 * 60)?,
+            _ => return None,
+        };
+        total = total.checked_add(Duration::from_secs(secs))?;
+        rest = &rest[unit_len..];
+    }
+    return Some(total);
+}
+
+/// Largest unit the duration can be written in without a remainder, e.g. `2m` or `1500ms`
+fn format_duration(duration: &Duration) -> String {
+    if duration.subsec_nanos() != 0 {
+        return format!("{}ms", duration.as_millis());
+    }
+    let secs = duration.as_secs();
+    for (unit, len) in [("d", 24 * 60 * 60), ("h", 60 * 60), ("m", 60)].iter() {
+        if secs != 0 && secs.is_multiple_of(*len) {
+            return format!("{}{}", secs / len, unit);
+        }
+    }
+    return format!("{}s", secs);
+}
+
+/// Checks for `scheme://host...`; only `file` URLs may leave out the host
+fn is_url(raw: &str) -> bool {
+    if raw.chars().any(char::is_whitespace) {
+        return false;
+    }
+    match raw.split_once("://") {
+        Some((scheme, rest)) => {
+            let valid_scheme = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+            valid_scheme && !rest.is_empty() && (!rest.starts_with('/') || scheme == "file")
+        },
+        None => false,
+    }
+}
+
+/// Parses the tokens of a custom subparam type, see `CliParameters::register_parser`
+pub trait ValueParser: Send + Sync {
+    /// Parses a token, the error describes what was expected instead
+    fn parse(&self, raw: &str) -> Result<Box<dyn Any + Send + Sync>, String>;
+}
+
+impl<F> ValueParser for F
+    where F: Fn(&str) -> Result<Box<dyn Any + Send + Sync>, String> + Send + Sync
+{
+    fn parse(&self, raw: &str) -> Result<Box<dyn Any + Send + Sync>, String> {
+        return self(raw);
+    }
+}
 
+/// Value parsed by a `ValueParser`, together with its token
 #[derive(Clone)]
+pub struct CustomValue {
+    type_name: String,
+    raw: String,
+    value: Arc<dyn Any + Send + Sync>,
+}
+
+impl CustomValue {
+    pub fn type_name(&self) -> &str {
+        return &self.type_name;
+    }
+
+    pub fn raw(&self) -> &str {
+        return &self.raw;
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        return self.value.downcast_ref();
+    }
+}
+
+fn serialize_custom<S: Serializer>(custom: &CustomValue, serializer: S) -> Result<S::Ok, S::Error> {
+    return serializer.serialize_str(&custom.raw);
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
 pub enum ParamValue {
     Array(Vec<String>),
     Int(i64),
     String(String),
     Num(f64),
+    Bool(bool),
+    // Read from specs as ints or strings and converted by `SubParam::validate`
+    #[serde(skip_deserializing)]
+    UInt(u64),
+    #[serde(skip_deserializing)]
+    Char(char),
+    #[serde(skip_deserializing)]
+    Path(PathBuf),
+    #[serde(skip_deserializing)]
+    Choice(String),
+    #[serde(skip_deserializing, serialize_with = "serialize_duration")]
+    Duration(Duration),
+    #[serde(skip_deserializing)]
+    Url(String),
+    /// Value of a custom type, written out as its token
+    #[serde(skip_deserializing, serialize_with = "serialize_custom")]
+    Custom(CustomValue),
 }
 
-impl std::string::ToString for ParamValue {
-    fn to_string(&self) -> String {
+impl Display for ParamValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            ParamValue::Array(args) => args.join(", "),
-            ParamValue::Int(val) => val.to_string(),
-            ParamValue::String(s) => s.clone(),
-            ParamValue::Num(num) => num.to_string(),
+            ParamValue::Array(args) => write!(f, "{}", args.join(", ")),
+            ParamValue::Int(val) => write!(f, "{}", val),
+            ParamValue::String(s) => write!(f, "{}", s),
+            ParamValue::Num(num) => write!(f, "{}", num),
+            ParamValue::Bool(val) => write!(f, "{}", val),
+            ParamValue::UInt(val) => write!(f, "{}", val),
+            ParamValue::Char(c) => write!(f, "{}", c),
+            ParamValue::Path(path) => write!(f, "{}", path.display()),
+            ParamValue::Choice(choice) => write!(f, "{}", choice),
+            ParamValue::Duration(duration) => write!(f, "{}", format_duration(duration)),
+            ParamValue::Url(url) => write!(f, "{}", url),
+            ParamValue::Custom(custom) => write!(f, "{}", custom.raw),
         }
     }
 }
 
-#[derive(Clone)]
-enum SubParamType {
+impl ParamValue {
+    /// Name of the subparam type this value was parsed as
+    pub fn type_name(&self) -> &str {
+        match self {
+            ParamValue::Array(_) => "array",
+            ParamValue::Int(_) => "int",
+            ParamValue::String(_) => "string",
+            ParamValue::Num(_) => "num",
+            ParamValue::Bool(_) => "bool",
+            ParamValue::UInt(_) => "uint",
+            ParamValue::Char(_) => "char",
+            ParamValue::Path(_) => "path",
+            ParamValue::Choice(_) => "choice",
+            ParamValue::Duration(_) => "duration",
+            ParamValue::Url(_) => "url",
+            ParamValue::Custom(custom) => &custom.type_name,
+        }
+    }
+
+    /// Payload of a custom value, if it is of type `T`
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        match self {
+            ParamValue::Custom(custom) => custom.downcast_ref(),
+            _ => None,
+        }
+    }
+
+    fn mismatch(&self, expected: &str) -> ClitcError {
+        return ClitcError::TypeMismatch {
+            name: String::new(),
+            expected: expected.to_string(),
+            found: self.type_name().to_string(),
+        };
+    }
+}
+
+impl TryFrom<&ParamValue> for i64 {
+    type Error = ClitcError;
+
+    fn try_from(val: &ParamValue) -> Result<i64, ClitcError> {
+        match val {
+            ParamValue::Int(val) => Ok(*val),
+            other => Err(other.mismatch("int")),
+        }
+    }
+}
+
+impl TryFrom<&ParamValue> for f64 {
+    type Error = ClitcError;
+
+    fn try_from(val: &ParamValue) -> Result<f64, ClitcError> {
+        match val {
+            ParamValue::Num(val) => Ok(*val),
+            other => Err(other.mismatch("num")),
+        }
+    }
+}
+
+impl TryFrom<&ParamValue> for String {
+    type Error = ClitcError;
+
+    fn try_from(val: &ParamValue) -> Result<String, ClitcError> {
+        match val {
+            // Choices and urls are only validated strings
+            ParamValue::String(val) | ParamValue::Choice(val) | ParamValue::Url(val) => Ok(val.clone()),
+            other => Err(other.mismatch("string")),
+        }
+    }
+}
+
+impl TryFrom<&ParamValue> for Vec<String> {
+    type Error = ClitcError;
+
+    fn try_from(val: &ParamValue) -> Result<Vec<String>, ClitcError> {
+        match val {
+            ParamValue::Array(val) => Ok(val.clone()),
+            other => Err(other.mismatch("array")),
+        }
+    }
+}
+
+impl TryFrom<&ParamValue> for bool {
+    type Error = ClitcError;
+
+    fn try_from(val: &ParamValue) -> Result<bool, ClitcError> {
+        match val {
+            ParamValue::Bool(val) => Ok(*val),
+            other => Err(other.mismatch("bool")),
+        }
+    }
+}
+
+impl TryFrom<&ParamValue> for u64 {
+    type Error = ClitcError;
+
+    fn try_from(val: &ParamValue) -> Result<u64, ClitcError> {
+        match val {
+            ParamValue::UInt(val) => Ok(*val),
+            other => Err(other.mismatch("uint")),
+        }
+    }
+}
+
+impl TryFrom<&ParamValue> for char {
+    type Error = ClitcError;
+
+    fn try_from(val: &ParamValue) -> Result<char, ClitcError> {
+        match val {
+            ParamValue::Char(c) => Ok(*c),
+            other => Err(other.mismatch("char")),
+        }
+    }
+}
+
+impl TryFrom<&ParamValue> for PathBuf {
+    type Error = ClitcError;
+
+    fn try_from(val: &ParamValue) -> Result<PathBuf, ClitcError> {
+        match val {
+            ParamValue::Path(path) => Ok(path.clone()),
+            other => Err(other.mismatch("path")),
+        }
+    }
+}
+
+impl TryFrom<&ParamValue> for Duration {
+    type Error = ClitcError;
+
+    fn try_from(val: &ParamValue) -> Result<Duration, ClitcError> {
+        match val {
+            ParamValue::Duration(duration) => Ok(*duration),
+            other => Err(other.mismatch("duration")),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubParamType {
     Array,
     Int,
     String,
     Num,
+    /// `true`/`false` or `yes`/`no`
+    Bool,
+    /// Path, which can be required to exist with `must_exist`
+    Path,
+    /// String out of the `choices` of the subparam
+    Choice,
+    /// Duration like `90`, `5s`, `2m` or `1h30m`
+    Duration,
+    UInt,
+    Char,
+    /// URL with scheme, e.g. `https://example.com`
+    Url,
+    /// Any other type name, parsed by the `ValueParser` registered for it
+    #[serde(untagged)]
+    Custom(String),
 }
 
 impl SubParamType {
+    /// Whether this is the custom type of the given name, built-in types never match
+    fn is_custom(&self, type_name: &str) -> bool {
+        return matches!(self, SubParamType::Custom(name) if name == type_name);
+    }
+
     pub fn info(&self) -> &str {
         match self {
             SubParamType::Array => "array",
             SubParamType::Int => "int",
             SubParamType::String => "string",
             SubParamType::Num => "num",
+            SubParamType::Bool => "bool",
+            SubParamType::Path => "path",
+            SubParamType::Choice => "choice",
+            SubParamType::Duration => "duration",
+            SubParamType::UInt => "uint",
+            SubParamType::Char => "char",
+            SubParamType::Url => "url",
+            SubParamType::Custom(name) => name,
         }
     }
 }
 
-impl SubParamType {
-    pub fn from(s: &str) -> Result<SubParamType, Box<Error>> {
-        if s == "array" {
-            return Ok(SubParamType::Array);
-        } else if s == "int" {
-            return Ok(SubParamType::Int);
-        } else if s == "string" {
-            return Ok(SubParamType::String);
-        } else if s == "num" {
-            return Ok(SubParamType::Num);
-        } else {
-            println!("Wrong subparam type!");
-            return Err(Box::new(WrongFormatError));
-        }
-    }
-}
-
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct SubParam {
     ord: u8,
-    name: Option<String>,
-    ptype: SubParamType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) name: Option<String>,
+    #[serde(rename = "type")]
+    pub(crate) ptype: SubParamType,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub(crate) required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) default: Option<ParamValue>,
+    /// Allowed values of a choice
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) choices: Vec<String>,
+    /// Only accept paths which exist
+    #[serde(default, skip_serializing_if = "is_false")]
+    must_exist: bool,
+    /// Inclusive range of int, num and uint values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max: Option<f64>,
+    /// Inclusive range of the number of characters of strings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_len: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_len: Option<usize>,
+    /// Regular expression strings have to match as a whole
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pattern: Option<String>,
+    /// Inclusive range of the number of elements of arrays
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_items: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_items: Option<usize>,
+    // Compiled `pattern`, set by `validate`
+    #[serde(skip)]
+    regex: Option<Regex>,
+    // Parser of a custom type, set by `CliParameters::register_parser`
+    #[serde(skip)]
+    parser: Option<Arc<dyn ValueParser>>,
+    /// Number of tokens taken by an array
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    arity: Option<Arity>,
+    /// Splits the tokens of an array into items, e.g. `,` for `a,b,c`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) delimiter: Option<String>,
+    /// Environment variable read as the token of this subparam, if it is not given as argument
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) env: Option<String>,
+    // Set for subparams built in code, which get their ord from their position
+    #[serde(skip)]
+    auto_ord: bool,
 }
 
 impl SubParam {
-    pub fn from(val: Value) -> Result<SubParam, Box<Error>> {
-        let ord = val["ord"].as_u64().map(|x| x as u8).ok_or(Box::new(MissingInformationError))?;
-        let name = val["name"].as_str().map(|x| x.to_string());
-        let ptype = SubParamType::from(val["type"].as_str().ok_or(Box::new(MissingInformationError))?)?;
+    pub fn new(name: &str, ptype: SubParamType) -> SubParam {
+        return SubParam {
+            ord: 0,
+            name: Some(name.to_string()),
+            ptype,
+            required: false,
+            default: None,
+            choices: Vec::new(),
+            must_exist: false,
+            min: None,
+            max: None,
+            min_len: None,
+            max_len: None,
+            pattern: None,
+            min_items: None,
+            max_items: None,
+            regex: None,
+            parser: None,
+            arity: None,
+            delimiter: None,
+            env: None,
+            auto_ord: true,
+        };
+    }
 
-        return Ok(SubParam{ord, name, ptype});
+    pub fn array(name: &str) -> SubParam {
+        return SubParam::new(name, SubParamType::Array);
     }
 
-    pub fn get_name(&self) -> String {
-        let ret;
-        if let Some(name) = self.name.clone() {
-            ret = name;
-        } else {
-            ret = self.ord.to_string();
-        }
-        return ret;
+    pub fn int(name: &str) -> SubParam {
+        return SubParam::new(name, SubParamType::Int);
     }
 
-    pub fn match_with(&self, args: &mut Vec<String>) -> Option<(String, ParamValue)> {
-        // Check associated Value
-        let ret = match &self.ptype {
-            SubParamType::Array => ParamValue::Array(args.clone()),
-            SubParamType::Int => {
-                let val = args.remove(0).parse::<i64>();
-                if val.is_err() {
-                    return None;
-                }
-                ParamValue::Int(val.unwrap())
-            },
-            SubParamType::Num => {
-                let val = args.remove(0).parse::<f64>();
-                if val.is_err() {
-                    return None;
-                }
-                ParamValue::Num(val.unwrap())
-            },
-            SubParamType::String => ParamValue::String(args.remove(0)),
-        };
+    pub fn string(name: &str) -> SubParam {
+        return SubParam::new(name, SubParamType::String);
+    }
 
-        return Some((self.get_name(), ret));
+    pub fn num(name: &str) -> SubParam {
+        return SubParam::new(name, SubParamType::Num);
     }
-}
 
-#[derive(Clone)]
-pub struct Param {
-    short: Option<String>,
-    pub name: String,
-    descr: Option<String>,
-    params: Vec<SubParam>,
-}
+    pub fn bool(name: &str) -> SubParam {
+        return SubParam::new(name, SubParamType::Bool);
+    }
 
-impl Param {
-    pub fn from(val: Value) -> Result<Param, Box<Error>> {
-        let short = val["short"].as_str().map(|x| x.to_string());
-        let name = val["name"].as_str().map(|x| x.to_string()).ok_or(Box::new(MissingInformationError))?;
-        let descr = val["descr"].as_str().map(|x| x.to_string());
-        let raw_params = val["params"].as_array();
+    pub fn path(name: &str) -> SubParam {
+        return SubParam::new(name, SubParamType::Path);
+    }
 
-        let mut ord_set = HashSet::new();
-        let mut params = Vec::new();
-        if raw_params.is_some() {
-            for v in raw_params.unwrap() {
-                let subparam = SubParam::from(v.clone())?;
-                let ord_cp = subparam.ord.clone();
-                if let Some(_) = ord_set.get(&ord_cp) {
-                    // ord not unique!
-                    println!("Ord not unique!");
-                    return Err(Box::new(WrongFormatError));
-                } else {
-                    ord_set.insert(ord_cp);
-                }
-                params.push(subparam);
-            }
-            params.sort_by(|a, b| a.ord.cmp(&b.ord));
-        }
+    pub fn choice(name: &str, choices: &[&str]) -> SubParam {
+        let mut subparam = SubParam::new(name, SubParamType::Choice);
+        subparam.choices = choices.iter().map(|x| x.to_string()).collect();
+        return subparam;
+    }
 
-        return Ok(Param{short, name, descr, params});
+    pub fn duration(name: &str) -> SubParam {
+        return SubParam::new(name, SubParamType::Duration);
     }
 
-    pub fn match_with(&self, args: &mut Vec<String>) -> (HashMap<String, ParamValue>) {
-        let mut ret = HashMap::new();
-        for param in self.params.iter() {
-            if args.len() > 0 {
-                let res = param.match_with(args);
-                if let Some((key, val)) = res {
-                    ret.insert(key, val);
-                }
-            }
-        }
-        return ret;
+    pub fn uint(name: &str) -> SubParam {
+        return SubParam::new(name, SubParamType::UInt);
     }
 
-    pub fn info(&self) -> Vec<String> {
-        let mut info: Vec<String> = Vec::new();
-        let mut name = self.name.clone();
-        if let Some(short) = self.short.clone() {
-            name.push_str(&format!("/ {}", short)[..]);
-        }
-        info.push(format!("\t{}\t{}", name, self.descr.clone().unwrap_or(String::new())));
-        for subparam in self.params.iter() {
-            let name = match subparam.name.clone() {
-                Some(val) => val,
-                None => subparam.ord.to_string(),
-            };
-            info.push(format!("\t\t{}:\t{}", name,subparam.ptype.info()));
-        }
-        return info;
+    pub fn char(name: &str) -> SubParam {
+        return SubParam::new(name, SubParamType::Char);
     }
-}
 
-pub struct CliParameters(Vec<Param>, bool);
+    pub fn url(name: &str) -> SubParam {
+        return SubParam::new(name, SubParamType::Url);
+    }
 
-impl CliParameters {
-    fn from(val: Value) -> Result<CliParameters, Box<Error>> {
-        if let Some(vec) = val["options"].as_array() {
-            let mut params = Vec::new();
-            for val in vec.iter() {
-                let param = Param::from(val.clone())?;
-                params.push(param);
-            }
-            return Ok(CliParameters(params, false));
-        }
-        println!("options root not found!");
-        return Err(Box::new(WrongFormatError));
+    /// Rejects paths which do not exist when parsing
+    pub fn must_exist(mut self) -> SubParam {
+        self.must_exist = true;
+        return self;
     }
 
-    pub fn from_str<'a>(data: &'a str) -> Result<CliParameters, Box<Error>> {
-        let val: Value = serde_json::from_str(data)?;
-        CliParameters::from(val)
+    /// Lowest accepted int, num or uint value
+    pub fn min(mut self, min: f64) -> SubParam {
+        self.min = Some(min);
+        return self;
     }
 
-    pub fn from_slice<'a>(data: &'a [u8]) -> Result<CliParameters, Box<Error>> {
-        let val: Value = serde_json::from_slice(data)?;
-        CliParameters::from(val)
+    /// Highest accepted int, num or uint value
+    pub fn max(mut self, max: f64) -> SubParam {
+        self.max = Some(max);
+        return self;
     }
 
-    pub fn from_reader<R>(reader: R) -> Result<CliParameters, Box<Error>>
-        where R: std::io::Read,
-    {
-        let val: Value = serde_json::from_reader(reader)?;
-        CliParameters::from(val)
+    pub fn min_len(mut self, min_len: usize) -> SubParam {
+        self.min_len = Some(min_len);
+        return self;
     }
 
-    pub fn set_sequential_processing(&mut self, seq: bool) {
-        self.1 = seq;
+    pub fn max_len(mut self, max_len: usize) -> SubParam {
+        self.max_len = Some(max_len);
+        return self;
     }
 
-    pub fn iter(&self) -> std::slice::Iter<Param> {
-        return self.0.iter();
+    /// Regular expression a string has to match as a whole
+    pub fn pattern(mut self, pattern: &str) -> SubParam {
+        self.pattern = Some(pattern.to_string());
+        return self;
     }
 
-    fn get_named_locations(&self, args: &Vec<String>) -> Vec<(usize, &Param)> {
-        let mut locations: Vec<(usize, &Param)> = Vec::new();
-        for (i, arg) in (0..args.len()).zip(args.iter()) {
-            let res = self.0.iter().find(|x| {
-                let mut ret: bool = false;
-                if x.short.is_some() {
-                    ret = x.short.clone().unwrap() == *arg
-                }
-                if !ret {
-                    ret = x.name.clone() == *arg
-                }
-                ret
-            });
-            // Param matched to argument:
-            if let Some(matching) = res {
-                locations.push((i, matching));
-            }
+    pub fn min_items(mut self, min_items: usize) -> SubParam {
+        self.min_items = Some(min_items);
+        return self;
+    }
+
+    pub fn max_items(mut self, max_items: usize) -> SubParam {
+        self.max_items = Some(max_items);
+        return self;
+    }
+
+    /// Number of tokens an array takes, `None` for no maximum
+    pub fn arity(mut self, min: usize, max: Option<usize>) -> SubParam {
+        self.arity = Some(Arity { min, max });
+        return self;
+    }
+
+    /// Splits the tokens of an array into items at the delimiter
+    pub fn delimiter(mut self, delimiter: &str) -> SubParam {
+        self.delimiter = Some(delimiter.to_string());
+        return self;
+    }
+
+    /// Environment variable used if the subparam is not given as argument
+    pub fn env(mut self, var: &str) -> SubParam {
+        self.env = Some(var.to_string());
+        return self;
+    }
+
+    /// Whether the argument is a number for an int or num subparam, even if it looks like an option
+    fn is_number(&self, arg: &str) -> bool {
+        match self.ptype {
+            SubParamType::Int => arg.parse::<i64>().is_ok(),
+            SubParamType::Num => arg.parse::<f64>().is_ok(),
+            _ => false,
         }
-        // Sort locations by index
-        locations.sort_by(|a, b| a.0.cmp(&b.0));
-        return locations;
     }
 
-    fn process_locations(&self, locations: Vec<(usize, &Param)>, args: &mut Vec<String>, ret: &mut HashMap<String, HashMap<String, ParamValue>>) {
-        for i in 0..locations.len() {
-            let (index, param) = locations[i];
-            let mut interval;
-            if (i + 1) < locations.len() {
-                let next_index = locations[i + 1].0;
-                interval = next_index - index;
-            } else {
-                interval = args.len();
-            }
-            // Leave out named identifier argument
-            args.remove(0);
-            interval -= 1;
-            // Parse Subparams
-            let res = param.match_with(&mut args.drain(..interval).collect());
-            ret.insert(param.name.clone(), res);
+    /// Parses the value of an environment variable as the single token of this subparam
+    fn match_env(&self, option: &str, var: &str, value: String) -> Result<(String, ParamValue), ClitcError> {
+        return self.match_with(option, &mut vec![(0, value)]).map_err(|e| e.from_env(var));
+    }
+
+    /// Parses a value of a config file, lists are the tokens of arrays or joined by their delimiter
+    fn match_config(&self, option: &str, config: &ConfigFile, key: &str, value: &Value) -> Result<(String, ParamValue), ClitcError> {
+        let mut tokens = config.tokens(key, value)?;
+        if tokens.is_empty() && self.ptype != SubParamType::Array {
+            return Err(config.invalid(key, "has no value"));
         }
+        if let (Some(delimiter), Value::Array(_), false) = (&self.delimiter, value, tokens.is_empty()) {
+            tokens = vec![(0, tokens.into_iter().map(|(_, x)| x).collect::<Vec<String>>().join(delimiter))];
+        }
+        let res = self.match_with(option, &mut tokens).map_err(|e| e.from_config(&config.path, key))?;
+        if !tokens.is_empty() {
+            return Err(config.invalid(key, "has too many values"));
+        }
+        return Ok(res);
     }
 
-    fn process_sequentially(&self, args: &mut Vec<String>, ret: &mut HashMap<String, HashMap<String, ParamValue>>) {
-        while args.len() > 0 {
-            // Iterate through arguments to find match with params
-            let res = (0..args.len()).zip(args.iter()).find_map(|(i, x)| {
-                let mut param = None;
-                // Compare to params
-                for p in self.0.iter() {
-                    let mut matched = false;
-                    if p.short.is_some() {
-                        matched = p.short.clone().unwrap() == **x;
-                    }
-                    if !matched {
-                        matched = p.name == **x;
-                    }
-                    if matched {
-                        // Return current argument index and matched param
-                        param = Some((i, p));
-                        break;
-                    }
-                }
-                return param;
+    pub fn ord(mut self, ord: u8) -> SubParam {
+        self.ord = ord;
+        self.auto_ord = false;
+        return self;
+    }
+
+    pub fn required(mut self) -> SubParam {
+        self.required = true;
+        return self;
+    }
+
+    pub fn default(mut self, val: ParamValue) -> SubParam {
+        self.default = Some(val);
+        return self;
+    }
+
+    fn validate(&mut self) -> Result<(), ClitcError> {
+        let is_choice = matches!(self.ptype, SubParamType::Choice);
+        if is_choice == self.choices.is_empty() {
+            return Err(ClitcError::WrongFormat {
+                path: String::from("choices"),
+                reason: String::from("choices are required for, and only supported by, type choice"),
             });
-            // Check if match was found
-            if let Some((index, matching)) = res {
-                // Cut off arguments to current location
-                let boundary = index + 1;
-                args.drain(..boundary);
-                // Parse Subparams
-                let result = matching.match_with(args);
-                ret.insert(matching.name.clone(), result);
+        }
+        if self.must_exist && !matches!(self.ptype, SubParamType::Path) {
+            return Err(ClitcError::WrongFormat {
+                path: String::from("must_exist"),
+                reason: String::from("must_exist is only supported by type path"),
+            });
+        }
+        self.validate_constraints()?;
+        // Only arrays can hold more than one token
+        let arity = self.get_arity();
+        let is_array = matches!(self.ptype, SubParamType::Array);
+        if !is_array && (arity.min != 1 || arity.max != Some(1)) {
+            return Err(ClitcError::WrongFormat {
+                path: String::from("arity"),
+                reason: format!("arity of type {} must be 1", self.ptype.info()),
+            });
+        }
+        match &self.delimiter {
+            Some(_) if !is_array => return Err(ClitcError::WrongFormat {
+                path: String::from("delimiter"),
+                reason: String::from("delimiter is only supported by type array"),
+            }),
+            Some(delimiter) if delimiter.is_empty() => return Err(ClitcError::WrongFormat {
+                path: String::from("delimiter"),
+                reason: String::from("delimiter must not be empty"),
+            }),
+            _ => {},
+        }
 
-            } else {
-                // Abort if no more can be found
-                break;
+        if let Some(default) = self.default.take() {
+            // Defaults are read as JSON values, types without JSON counterpart are parsed like tokens
+            let converted = match (&default, &self.ptype) {
+                (ParamValue::Array(_), SubParamType::Array)
+                | (ParamValue::String(_), SubParamType::String)
+                | (ParamValue::Int(_), SubParamType::Int)
+                | (ParamValue::Num(_), SubParamType::Num)
+                | (ParamValue::Bool(_), SubParamType::Bool) => Some(default.clone()),
+                // Whole numbers are valid defaults for num and uint
+                (ParamValue::Int(_), SubParamType::Num) | (ParamValue::Int(_), SubParamType::UInt) => self.parse_value(&default.to_string()),
+                // Converted once the parser is registered
+                (_, SubParamType::Custom(_)) => Some(default.clone()),
+                (ParamValue::String(raw), SubParamType::Path)
+                | (ParamValue::String(raw), SubParamType::Choice)
+                | (ParamValue::String(raw), SubParamType::Duration)
+                | (ParamValue::String(raw), SubParamType::Char)
+                | (ParamValue::String(raw), SubParamType::Url) => self.parse_value(raw),
+                _ => None,
+            };
+            match converted {
+                Some(val) => self.default = Some(val),
+                None => return Err(ClitcError::WrongFormat {
+                    path: String::from("default"),
+                    reason: format!("default value `{}` is not of type {}", default, self.type_info()),
+                }),
+            }
+        }
+        if let Some(default) = &self.default {
+            if let Some(reason) = self.check_constraints(default) {
+                return Err(ClitcError::WrongFormat {
+                    path: String::from("default"),
+                    reason: format!("default value `{}` {}", default, reason),
+                });
             }
         }
+        return Ok(());
     }
 
-    pub fn parse_vec(&self, mut args: Vec<String>) -> HashMap<String, HashMap<String, ParamValue>> {
-        let mut ret: HashMap<String, HashMap<String, ParamValue>> = HashMap::new();
-
-        if self.1 { // checking set sequentiality member variable
-            self.process_sequentially(&mut args, &mut ret);
-        } else {
-            // Get param locations
-            let locations = self.get_named_locations(&args);
-
-            // Process named parameters
-            self.process_locations(locations, &mut args, &mut ret);
+    /// Checks that constraints fit the type and compiles the pattern
+    fn validate_constraints(&mut self) -> Result<(), ClitcError> {
+        let numeric = matches!(self.ptype, SubParamType::Int | SubParamType::Num | SubParamType::UInt);
+        let string = matches!(self.ptype, SubParamType::String);
+        let array = matches!(self.ptype, SubParamType::Array);
+        let constraints = [
+            ("min", self.min.is_some(), numeric),
+            ("max", self.max.is_some(), numeric),
+            ("min_len", self.min_len.is_some(), string),
+            ("max_len", self.max_len.is_some(), string),
+            ("pattern", self.pattern.is_some(), string),
+            ("min_items", self.min_items.is_some(), array),
+            ("max_items", self.max_items.is_some(), array),
+        ];
+        for (key, set, supported) in constraints.iter() {
+            if *set && !*supported {
+                return Err(ClitcError::WrongFormat {
+                    path: key.to_string(),
+                    reason: format!("{} is not supported by type {}", key, self.ptype.info()),
+                });
+            }
         }
+        check_bounds(self.min, self.max, "max")?;
+        check_bounds(self.min_len, self.max_len, "max_len")?;
+        check_bounds(self.min_items, self.max_items, "max_items")?;
 
-        return ret;
+        if let Some(pattern) = &self.pattern {
+            // Errors refer to the pattern as written in the spec
+            match Regex::new(pattern).and_then(|_| Regex::new(&format!("^(?:{})$", pattern))) {
+                Ok(regex) => self.regex = Some(regex),
+                Err(err) => return Err(ClitcError::WrongFormat {
+                    path: String::from("pattern"),
+                    reason: err.to_string(),
+                }),
+            }
+        }
+        return Ok(());
     }
 
-    pub fn parse_args(&self) -> HashMap<String, HashMap<String, ParamValue>> {
+    /// Description of the first constraint the value violates
+    fn check_constraints(&self, val: &ParamValue) -> Option<String> {
+        match val {
+            ParamValue::Int(n) => self.check_range(*n as f64),
+            ParamValue::Num(n) => self.check_range(*n),
+            ParamValue::UInt(n) => self.check_range(*n as f64),
+            ParamValue::String(s) => {
+                let len = s.chars().count();
+                if let Some(min_len) = self.min_len.filter(|x| len < *x) {
+                    return Some(format!("must be at least {} characters long", min_len));
+                }
+                if let Some(max_len) = self.max_len.filter(|x| len > *x) {
+                    return Some(format!("must be at most {} characters long", max_len));
+                }
+                if let (Some(regex), Some(pattern)) = (&self.regex, &self.pattern) {
+                    if !regex.is_match(s) {
+                        return Some(format!("must match `{}`", pattern));
+                    }
+                }
+                None
+            },
+            ParamValue::Array(vec) => {
+                if let Some(min_items) = self.min_items.filter(|x| vec.len() < *x) {
+                    return Some(format!("must have at least {} items", min_items));
+                }
+                if let Some(max_items) = self.max_items.filter(|x| vec.len() > *x) {
+                    return Some(format!("must have at most {} items", max_items));
+                }
+                None
+            },
+            _ => None,
+        }
+    }
 
-        let mut args: Vec<String> = std::env::args().collect();
-        args.remove(0); // remove program path cl argument
+    fn check_range(&self, n: f64) -> Option<String> {
+        if let Some(min) = self.min.filter(|x| n < *x) {
+            return Some(format!("must be at least {}", min));
+        }
+        if let Some(max) = self.max.filter(|x| n > *x) {
+            return Some(format!("must be at most {}", max));
+        }
+        return None;
+    }
 
-        return self.parse_vec(args);
+    /// Number of tokens taken; arrays take all of their tokens by default, or a single one with a delimiter
+    pub fn get_arity(&self) -> Arity {
+        match (self.arity, &self.ptype, &self.delimiter) {
+            (Some(arity), _, _) => arity,
+            (None, SubParamType::Array, None) => Arity { min: 0, max: None },
+            (None, _, _) => Arity { min: 1, max: Some(1) },
+        }
     }
 
-    pub fn parse_str<'a, F>(&self, data: &'a str, split: F) -> HashMap<String, HashMap<String, ParamValue>>
-        where F: Fn(&'a str) -> (Vec<String>)
-    {
-        let args: Vec<String> = split(data);
-        self.parse_vec(args)
+    /// Type name for help and errors, including the choices
+    pub fn type_info(&self) -> String {
+        match self.ptype {
+            SubParamType::Choice => format!("one of {}", self.choices.join(", ")),
+            SubParamType::Path if self.must_exist => String::from("existing path"),
+            _ => self.ptype.info().to_string(),
+        }
     }
 
-    pub fn parse_str_whitespace<'a>(&self, data: &'a str) -> HashMap<String, HashMap<String, ParamValue>> {
-        self.parse_str(data, |args| args.split_whitespace().map(|x| x.to_string()).collect())
+    /// Parses a single token as the type of this subparam
+    fn parse_value(&self, raw: &str) -> Option<ParamValue> {
+        match self.ptype {
+            // Arrays take several tokens, see `match_with`
+            SubParamType::Array => None,
+            SubParamType::Int => raw.parse().ok().map(ParamValue::Int),
+            SubParamType::String => Some(ParamValue::String(raw.to_string())),
+            SubParamType::Num => raw.parse().ok().map(ParamValue::Num),
+            SubParamType::Bool => match &raw.to_lowercase()[..] {
+                "true" | "yes" => Some(ParamValue::Bool(true)),
+                "false" | "no" => Some(ParamValue::Bool(false)),
+                _ => None,
+            },
+            SubParamType::Path => Some(ParamValue::Path(PathBuf::from(raw))),
+            SubParamType::Choice => self.choices.iter().find(|x| *x == raw).map(|x| ParamValue::Choice(x.clone())),
+            SubParamType::Duration => parse_duration(raw).map(ParamValue::Duration),
+            SubParamType::UInt => raw.parse().ok().map(ParamValue::UInt),
+            SubParamType::Char => {
+                let mut chars = raw.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(ParamValue::Char(c)),
+                    _ => None,
+                }
+            },
+            SubParamType::Url if is_url(raw) => Some(ParamValue::Url(raw.to_string())),
+            SubParamType::Url => None,
+            SubParamType::Custom(_) => self.parse_custom(raw).ok(),
+        }
+    }
+
+    pub fn get_name(&self) -> String {
+        let ret;
+        if let Some(name) = self.name.clone() {
+            ret = name;
+        } else {
+            ret = self.ord.to_string();
+        }
+        return ret;
+    }
+
+    /// Parses a token with the registered parser, the error describes what was expected
+    fn parse_custom(&self, raw: &str) -> Result<ParamValue, String> {
+        let type_name = self.ptype.info().to_string();
+        let parser = self.parser.as_ref().ok_or_else(|| String::from("no parser registered"))?;
+        let value = parser.parse(raw).map_err(|e| format!("{} ({})", type_name, e))?;
+        return Ok(ParamValue::Custom(CustomValue { type_name, raw: raw.to_string(), value: Arc::from(value) }));
+    }
+
+    /// Sets the parser of a custom type and converts the default with it
+    fn set_parser(&mut self, parser: &Arc<dyn ValueParser>) -> Result<(), ClitcError> {
+        self.parser = Some(Arc::clone(parser));
+        if let Some(default) = &self.default {
+            match self.parse_custom(&default.to_string()) {
+                Ok(val) => self.default = Some(val),
+                Err(expected) => return Err(ClitcError::WrongFormat {
+                    path: String::from("default"),
+                    reason: format!("default value `{}` is not of type {}", default, expected),
+                }),
+            }
+        }
+        return Ok(());
+    }
+
+    fn invalid_value(&self, option: &str, index: usize, raw: String) -> ClitcError {
+        return ClitcError::InvalidValue {
+            option: option.to_string(),
+            subparam: self.get_name(),
+            index,
+            raw,
+            expected: self.type_info(),
+        };
+    }
+
+    /// Consumes the tokens belonging to this subparam from `args`.
+    /// Each token carries its index in the original argument list, so that parse
+    /// errors can point at the offending argument.
+    pub fn match_with(&self, option: &str, args: &mut Vec<(usize, String)>) -> Result<(String, ParamValue), ClitcError> {
+        return self.match_named(option, &format!("{} {}", option, self.get_name()), args);
+    }
+
+    /// Like `match_with`, `name` identifies the subparam in arity errors
+    fn match_named(&self, option: &str, name: &str, args: &mut Vec<(usize, String)>) -> Result<(String, ParamValue), ClitcError> {
+        // Every type but arrays needs a token, arrays check their arity below
+        if args.is_empty() && self.ptype != SubParamType::Array {
+            return Err(ClitcError::WrongArity { name: name.to_string(), expected: self.get_arity().to_string(), found: 0 });
+        }
+        // Check associated Value
+        let (index, raw, ret) = match &self.ptype {
+            SubParamType::Array => {
+                let arity = self.get_arity();
+                let count = match arity.max {
+                    Some(max) => max.min(args.len()),
+                    None => args.len(),
+                };
+                if count < arity.min {
+                    return Err(ClitcError::WrongArity { name: name.to_string(), expected: arity.to_string(), found: count });
+                }
+                let index = args.first().map(|x| x.0).unwrap_or_default();
+                let tokens: Vec<String> = args.drain(..count).map(|(_, x)| x).collect();
+                let raw = tokens.join(" ");
+                let vec = match &self.delimiter {
+                    Some(delimiter) => tokens.iter().flat_map(|x| x.split(&delimiter[..])).map(|x| x.to_string()).collect(),
+                    None => tokens,
+                };
+                (index, raw, ParamValue::Array(vec))
+            },
+            SubParamType::Custom(name) => {
+                let (index, raw) = args.remove(0);
+                if self.parser.is_none() {
+                    return Err(ClitcError::UnknownType { name: name.clone() });
+                }
+                match self.parse_custom(&raw) {
+                    Ok(val) => (index, raw, val),
+                    Err(expected) => return Err(ClitcError::InvalidValue {
+                        option: option.to_string(),
+                        subparam: self.get_name(),
+                        index,
+                        raw,
+                        expected,
+                    }),
+                }
+            },
+            _ => {
+                let (index, raw) = args.remove(0);
+                match self.parse_value(&raw) {
+                    Some(ParamValue::Path(path)) if self.must_exist && !path.exists() => {
+                        return Err(self.invalid_value(option, index, raw));
+                    },
+                    Some(val) => (index, raw, val),
+                    None => return Err(self.invalid_value(option, index, raw)),
+                }
+            },
+        };
+        if let Some(reason) = self.check_constraints(&ret) {
+            return Err(ClitcError::ConstraintViolation {
+                option: option.to_string(),
+                subparam: self.get_name(),
+                index,
+                raw,
+                reason,
+            });
+        }
+
+        return Ok((self.get_name(), ret));
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Param {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) short: Option<String>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) descr: Option<String>,
+    #[serde(default)]
+    pub(crate) params: Vec<SubParam>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub(crate) required: bool,
+    #[serde(default, skip_serializing_if = "Multiple::is_last")]
+    pub(crate) multiple: Multiple,
+    /// Environment variable holding the arguments of the option, if it is not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) env: Option<String>,
+    /// Section of the help page listing the option
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) group: Option<String>,
+}
+
+/// How repeated occurrences of an option are kept
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(try_from = "MultipleRepr", into = "MultipleRepr")]
+pub enum Multiple {
+    /// Later occurrences replace earlier ones
+    #[default]
+    Last,
+    /// Every occurrence is kept, see `ParseResult::occurrences`
+    Collect,
+    /// Occurrences of an option without subparams are counted as its `count` value
+    Count,
+}
+
+impl Multiple {
+    fn is_last(&self) -> bool {
+        return *self == Multiple::Last;
+    }
+}
+
+/// Spec representation of `multiple`, either `true`/`false` or `"count"`
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum MultipleRepr {
+    Flag(bool),
+    Mode(String),
+}
+
+impl TryFrom<MultipleRepr> for Multiple {
+    type Error = String;
+
+    fn try_from(repr: MultipleRepr) -> Result<Multiple, String> {
+        match repr {
+            MultipleRepr::Flag(false) => Ok(Multiple::Last),
+            MultipleRepr::Flag(true) => Ok(Multiple::Collect),
+            MultipleRepr::Mode(mode) if mode == "count" => Ok(Multiple::Count),
+            MultipleRepr::Mode(mode) => Err(format!("unknown multiple mode `{}`, expected true, false or \"count\"", mode)),
+        }
+    }
+}
+
+impl From<Multiple> for MultipleRepr {
+    fn from(multiple: Multiple) -> MultipleRepr {
+        match multiple {
+            Multiple::Last => MultipleRepr::Flag(false),
+            Multiple::Collect => MultipleRepr::Flag(true),
+            Multiple::Count => MultipleRepr::Mode(String::from("count")),
+        }
+    }
+}
+
+impl Param {
+    pub fn new(name: &str) -> Param {
+        return Param {
+            short: None,
+            name: name.to_string(),
+            descr: None,
+            params: Vec::new(),
+            required: false,
+            multiple: Multiple::Last,
+            env: None,
+            group: None,
+        };
+    }
+
+    pub fn short(mut self, short: &str) -> Param {
+        self.short = Some(short.to_string());
+        return self;
+    }
+
+    pub fn descr(mut self, descr: &str) -> Param {
+        self.descr = Some(descr.to_string());
+        return self;
+    }
+
+    pub fn required(mut self) -> Param {
+        self.required = true;
+        return self;
+    }
+
+    /// Keeps every occurrence of the option instead of only the last one
+    pub fn multiple(mut self) -> Param {
+        self.multiple = Multiple::Collect;
+        return self;
+    }
+
+    /// Counts the occurrences of a flag, e.g. `-vvv`
+    pub fn count(mut self) -> Param {
+        self.multiple = Multiple::Count;
+        return self;
+    }
+
+    /// Environment variable used if the option is not given.
+    /// Its value is split at whitespace into the arguments of the subparams; flags are set by any value but `0`, `false`, `no` and `off`.
+    pub fn env(mut self, var: &str) -> Param {
+        self.env = Some(var.to_string());
+        return self;
+    }
+
+    /// Lists the option under its own section of the help page, e.g. `"Network"`
+    pub fn group(mut self, group: &str) -> Param {
+        self.group = Some(group.to_string());
+        return self;
+    }
+
+    /// Adds a subparam, ordered after the previous ones unless it has an explicit ord
+    pub fn arg(mut self, mut subparam: SubParam) -> Param {
+        if subparam.auto_ord {
+            subparam.ord = self.params.len() as u8;
+        }
+        self.params.push(subparam);
+        return self;
+    }
+
+    /// Checks the subparams and brings them into order
+    fn validate(&mut self) -> Result<(), ClitcError> {
+        if self.multiple == Multiple::Count && !self.params.is_empty() {
+            return Err(ClitcError::WrongFormat {
+                path: String::from("multiple"),
+                reason: String::from("counting is only supported for options without params"),
+            });
+        }
+        let mut ord_set = HashSet::new();
+        for (i, subparam) in self.params.iter_mut().enumerate() {
+            subparam.validate().map_err(|e| e.within(&format!("params[{}]", i)))?;
+            if !ord_set.insert(subparam.ord) {
+                // ord not unique!
+                return Err(ClitcError::DuplicateOrd { option: self.name.clone(), ord: subparam.ord });
+            }
+        }
+        self.params.sort_by_key(|x| x.ord);
+        return Ok(());
+    }
+
+    pub fn match_with(&self, args: &mut Vec<(usize, String)>) -> Result<HashMap<String, ParamValue>, ClitcError> {
+        let values = self.match_indexed(args)?;
+        return Ok(values.into_iter().map(|(key, (_, val))| (key, val)).collect());
+    }
+
+    /// Matches the subparams like `match_with`, keeping the argument index of the first token of every value
+    fn match_indexed(&self, args: &mut Vec<(usize, String)>) -> Result<HashMap<String, (usize, ParamValue)>, ClitcError> {
+        let mut ret = HashMap::new();
+        for param in self.params.iter() {
+            if let Some((index, _)) = args.first() {
+                let index = *index;
+                let (key, val) = param.match_with(&self.name, args)?;
+                ret.insert(key, (index, val));
+            } else if param.default.is_none() && param.arity.map(|x| x.min > 0).unwrap_or(false) {
+                // Arrays with an explicit arity need their tokens
+                return Err(ClitcError::WrongArity {
+                    name: format!("{} {}", self.name, param.get_name()),
+                    expected: param.get_arity().to_string(),
+                    found: 0,
+                });
+            }
+        }
+        return Ok(ret);
+    }
+
+    fn set_parser(&mut self, type_name: &str, parser: &Arc<dyn ValueParser>) -> Result<(), ClitcError> {
+        for (i, subparam) in self.params.iter_mut().enumerate() {
+            if subparam.ptype.is_custom(type_name) {
+                subparam.set_parser(parser).map_err(|e| e.within(&format!("params[{}]", i)))?;
+            }
+        }
+        return Ok(());
+    }
+
+    /// Values of one occurrence of the option in a config file, with the keys they were read from
+    fn match_config(&self, config: &ConfigFile, key: &str, value: &Value) -> Result<Vec<(String, ParamValue, String)>, ClitcError> {
+        if let Value::Object(values) = value {
+            let mut ret = Vec::new();
+            for (subname, value) in values.iter() {
+                let subkey = format!("{}.{}", key, subname);
+                let subparam = match self.params.iter().find(|x| x.get_name() == *subname) {
+                    Some(subparam) => subparam,
+                    None => return Err(config.invalid(&subkey, "is not a subparam of the option")),
+                };
+                let (subname, val) = subparam.match_config(&self.name, config, &subkey, value)?;
+                ret.push((subname, val, subkey));
+            }
+            return Ok(ret);
+        }
+        if self.params.len() == 1 {
+            let (subname, val) = self.params[0].match_config(&self.name, config, key, value)?;
+            return Ok(vec![(subname, val, key.to_string())]);
+        }
+        // Lists are the arguments of the option
+        let mut tokens = config.tokens(key, value)?;
+        let values = self.match_with(&mut tokens).map_err(|e| e.from_config(&config.path, key))?;
+        if !tokens.is_empty() {
+            return Err(config.invalid(key, "has too many values"));
+        }
+        return Ok(values.into_iter().map(|(subname, val)| (subname, val, key.to_string())).collect());
+    }
+
+    /// Number of leading arguments which are numbers for int or num subparams, e.g. `-5` for an int
+    fn numeric_values(&self, args: &[(usize, String)]) -> usize {
+        let mut count = 0;
+        for (param, (_, arg)) in self.params.iter().zip(args.iter()) {
+            if !param.is_number(arg) {
+                break;
+            }
+            count += 1;
+        }
+        return count;
+    }
+
+    /// Collects required items missing from the parsed values of this param
+    fn missing_required(&self, values: Option<&HashMap<String, ParamValue>>, missing: &mut Vec<(String, Option<String>)>) {
+        match values {
+            Some(values) => {
+                for subparam in self.params.iter() {
+                    let name = subparam.get_name();
+                    if subparam.required && !values.contains_key(&name) {
+                        missing.push((self.name.clone(), Some(name)));
+                    }
+                }
+            },
+            None => {
+                if self.required {
+                    missing.push((self.name.clone(), None));
+                }
+            },
+        }
+    }
+
+    pub fn info(&self) -> Vec<String> {
+        let mut info: Vec<String> = Vec::new();
+        let mut name = self.name.clone();
+        if let Some(short) = self.short.clone() {
+            name.push_str(&format!("/ {}", short)[..]);
+        }
+        if self.required {
+            name.push_str(" (required)");
+        }
+        if !self.multiple.is_last() {
+            name.push_str(" (repeatable)");
+        }
+        if let Some(var) = &self.env {
+            name.push_str(&format!(" (env: {})", var));
+        }
+        info.push(format!("\t{}\t{}", name, self.descr.clone().unwrap_or_default()));
+        for subparam in self.params.iter() {
+            let name = match subparam.name.clone() {
+                Some(val) => val,
+                None => subparam.ord.to_string(),
+            };
+            let required = if subparam.required { " (required)" } else { "" };
+            let default = match &subparam.default {
+                Some(val) => format!(" (default: {})", val),
+                None => String::new(),
+            };
+            let env = match &subparam.env {
+                Some(var) => format!(" (env: {})", var),
+                None => String::new(),
+            };
+            info.push(format!("\t\t{}:\t{}{}{}{}", name, subparam.type_info(), required, default, env));
+        }
+        return info;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "ArityRepr", into = "ArityRepr")]
+pub struct Arity {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+/// Spec representation of an arity, either a count or a [min, max] range
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ArityRepr {
+    Exact(usize),
+    Range(usize, Option<usize>),
+}
+
+impl TryFrom<ArityRepr> for Arity {
+    type Error = String;
+
+    fn try_from(repr: ArityRepr) -> Result<Arity, String> {
+        let arity = match repr {
+            ArityRepr::Exact(n) => Arity { min: n, max: Some(n) },
+            ArityRepr::Range(min, max) => Arity { min, max },
+        };
+        if arity.max.map(|max| max < arity.min).unwrap_or(false) {
+            return Err(format!("arity maximum {} is lower than minimum {}", arity.max.unwrap(), arity.min));
+        }
+        return Ok(arity);
+    }
+}
+
+impl From<Arity> for ArityRepr {
+    fn from(arity: Arity) -> ArityRepr {
+        match arity.max {
+            Some(max) if max == arity.min => ArityRepr::Exact(max),
+            max => ArityRepr::Range(arity.min, max),
+        }
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "exactly {}", max),
+            Some(max) => write!(f, "{} to {}", self.min, max),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
+}
+
+/// Unnamed argument identified by its position among the tokens not used by any option
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Positional {
+    pub(crate) param: SubParam,
+}
+
+impl Positional {
+    pub fn new(param: SubParam) -> Positional {
+        return Positional{param};
+    }
+
+    pub fn arity(mut self, min: usize, max: Option<usize>) -> Positional {
+        self.param.arity = Some(Arity { min, max });
+        return self;
+    }
+
+    /// Number of tokens taken, arrays take all remaining tokens by default
+    pub fn get_arity(&self) -> Arity {
+        return self.param.get_arity();
+    }
+
+    fn validate(&mut self) -> Result<(), ClitcError> {
+        return self.param.validate();
+    }
+
+    pub fn get_name(&self) -> String {
+        return self.param.get_name();
+    }
+
+    /// Consumes the tokens belonging to this positional from the front of `args`
+    pub fn match_with(&self, args: &mut Vec<(usize, String)>) -> Result<Option<(String, ParamValue)>, ClitcError> {
+        // Arrays with an explicit arity need their tokens, like subparams of options
+        let needed = self.param.default.is_none() && self.param.arity.is_some_and(|x| x.min > 0);
+        if args.is_empty() && !needed {
+            return Ok(None);
+        }
+        let res = self.param.match_named("positional", &self.get_name(), args)?;
+        return Ok(Some(res));
+    }
+}
+
+/// Reads an environment variable by name
+type EnvLookup = dyn Fn(&str) -> Option<String>;
+
+/// Command of a path with its arguments
+type Segment<'a> = (Cow<'a, Command>, Vec<(usize, String)>);
+
+/// Where a value of a `ParseResult` came from
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    /// Command line argument at the given index
+    Cli(usize),
+    /// Environment variable of the given name
+    Env(String),
+    /// Default value of the spec
+    Default,
+    /// Key of a config file
+    Config { file: String, key: String },
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Cli(index) => write!(f, "command line (arg {})", index),
+            Source::Env(var) => write!(f, "env {}", var),
+            Source::Default => write!(f, "default"),
+            Source::Config { file, key } => write!(f, "config {} ({})", file, key),
+        }
+    }
+}
+
+/// Flag values from the environment are set unless they are empty, `0`, `false`, `no` or `off`
+fn is_truthy(value: &str) -> bool {
+    return !matches!(&value.trim().to_lowercase()[..], "" | "0" | "false" | "no" | "off");
+}
+
+/// Values parsed from a list of arguments
+#[derive(Clone, Default)]
+pub struct ParseResult {
+    /// Names of the (nested) subcommands matched by the leading arguments
+    pub command: Vec<String>,
+    /// Subparam values of every option found, keyed by option name
+    pub options: HashMap<String, HashMap<String, ParamValue>>,
+    /// Values of the positionals, keyed by positional name
+    pub positionals: HashMap<String, ParamValue>,
+    /// Tokens that could be assigned neither to an option nor to a positional
+    pub unmatched: Vec<String>,
+    /// Option names keyed by their short identifier
+    aliases: HashMap<String, String>,
+    /// Every occurrence of options with `multiple` set, keyed by option name
+    occurrences: HashMap<String, Vec<HashMap<String, ParamValue>>>,
+    /// Number of occurrences of every option found
+    counts: HashMap<String, usize>,
+    /// Sources of the options, of their subparam values and of the positionals
+    option_sources: HashMap<String, Source>,
+    sources: HashMap<String, HashMap<String, Source>>,
+    positional_sources: HashMap<String, Source>,
+}
+
+impl ParseResult {
+    /// Records an occurrence of an option given at argument `index` according to its `multiple` setting
+    fn insert_option(&mut self, param: &Param, index: usize, indexed: HashMap<String, (usize, ParamValue)>) {
+        let mut sources: HashMap<String, Source> = indexed.iter().map(|(key, (index, _))| (key.clone(), Source::Cli(*index))).collect();
+        let mut values: HashMap<String, ParamValue> = indexed.into_iter().map(|(key, (_, val))| (key, val)).collect();
+        let count = self.counts.entry(param.name.clone()).or_insert(0);
+        *count += 1;
+        match param.multiple {
+            Multiple::Last => {},
+            Multiple::Collect => self.occurrences.entry(param.name.clone()).or_default().push(values.clone()),
+            Multiple::Count => {
+                values.insert(String::from("count"), ParamValue::Int(*count as i64));
+                sources.insert(String::from("count"), Source::Cli(index));
+            },
+        }
+        self.option_sources.insert(param.name.clone(), Source::Cli(index));
+        self.sources.insert(param.name.clone(), sources);
+        self.options.insert(param.name.clone(), values);
+    }
+
+    /// Sets a flag from a fallback source with the given count, unless it is already set
+    fn insert_flag(&mut self, param: &Param, count: i64, source: Source) {
+        if count <= 0 || self.options.contains_key(&param.name) {
+            return;
+        }
+        self.counts.insert(param.name.clone(), count as usize);
+        self.options.insert(param.name.clone(), HashMap::new());
+        self.option_sources.insert(param.name.clone(), source.clone());
+        if param.multiple == Multiple::Count {
+            self.fill_value(&param.name, String::from("count"), ParamValue::Int(count), source);
+        }
+    }
+
+    /// Sets an option from a fallback source, unless it is already set
+    fn insert_values(&mut self, param: &Param, values: HashMap<String, ParamValue>, source: Source) {
+        if self.options.contains_key(&param.name) {
+            return;
+        }
+        self.counts.insert(param.name.clone(), 1);
+        self.options.insert(param.name.clone(), HashMap::new());
+        self.option_sources.insert(param.name.clone(), source.clone());
+        for (key, val) in values.into_iter() {
+            self.fill_value(&param.name, key, val, source.clone());
+        }
+    }
+
+    /// Sets every occurrence of a repeated option from a fallback source, unless it is already set
+    fn insert_occurrences(&mut self, param: &Param, occurrences: Vec<HashMap<String, ParamValue>>, source: Source) {
+        let last = match occurrences.last() {
+            Some(last) if !self.options.contains_key(&param.name) => last.clone(),
+            _ => return,
+        };
+        self.counts.insert(param.name.clone(), occurrences.len());
+        self.option_sources.insert(param.name.clone(), source.clone());
+        self.sources.insert(param.name.clone(), last.keys().map(|x| (x.clone(), source.clone())).collect());
+        self.options.insert(param.name.clone(), last);
+        self.occurrences.insert(param.name.clone(), occurrences);
+    }
+
+    /// Takes over options, subparam values and positionals which are not set yet from the result of another layer
+    fn merge(&mut self, other: ParseResult) {
+        let mut sources = other.sources;
+        for (option, values) in other.options.into_iter() {
+            let mut value_sources = sources.remove(&option).unwrap_or_default();
+            // Every value of a parsed layer has a source, the one of its option is only a fallback
+            let option_source = other.option_sources.get(&option).cloned().unwrap_or(Source::Default);
+            if !self.options.contains_key(&option) {
+                if let Some(occurrences) = other.occurrences.get(&option) {
+                    self.occurrences.insert(option.clone(), occurrences.clone());
+                }
+                if let Some(count) = other.counts.get(&option) {
+                    self.counts.insert(option.clone(), *count);
+                }
+                self.option_sources.insert(option.clone(), option_source.clone());
+                self.options.insert(option.clone(), HashMap::new());
+            }
+            for (key, val) in values.into_iter() {
+                let source = value_sources.remove(&key).unwrap_or_else(|| option_source.clone());
+                self.fill_value(&option, key, val, source);
+            }
+        }
+        let mut sources = other.positional_sources;
+        for (name, val) in other.positionals.into_iter() {
+            let source = sources.remove(&name).unwrap_or(Source::Default);
+            self.fill_positional(name, val, source);
+        }
+    }
+
+    /// Sets a subparam value from a fallback source, unless the option already has one.
+    /// Every occurrence of options with `multiple` set gets the value as well.
+    fn fill_value(&mut self, option: &str, subparam: String, val: ParamValue, source: Source) {
+        for occurrence in self.occurrences.get_mut(option).into_iter().flatten() {
+            occurrence.entry(subparam.clone()).or_insert_with(|| val.clone());
+        }
+        let values = self.options.entry(option.to_string()).or_default();
+        if values.contains_key(&subparam) {
+            return;
+        }
+        values.insert(subparam.clone(), val);
+        self.counts.entry(option.to_string()).or_insert(1);
+        self.option_sources.entry(option.to_string()).or_insert_with(|| source.clone());
+        self.sources.entry(option.to_string()).or_default().insert(subparam, source);
+    }
+
+    fn has_value(&self, option: &str, subparam: &str) -> bool {
+        return self.options.get(option).map(|x| x.contains_key(subparam)).unwrap_or(false);
+    }
+
+    fn fill_positional(&mut self, name: String, val: ParamValue, source: Source) {
+        if !self.positionals.contains_key(&name) {
+            self.positionals.insert(name.clone(), val);
+            self.positional_sources.insert(name, source);
+        }
+    }
+
+    /// Where the option came from, which is the command line if it was given there
+    pub fn option_source(&self, option: &str) -> Option<&Source> {
+        return self.option_key(option).and_then(|x| self.option_sources.get(x));
+    }
+
+    /// Where the value of a subparam came from
+    pub fn source(&self, option: &str, subparam: &str) -> Option<&Source> {
+        return self.option_key(option).and_then(|x| self.sources.get(x)).and_then(|x| x.get(subparam));
+    }
+
+    pub fn positional_source(&self, name: &str) -> Option<&Source> {
+        return self.positional_sources.get(name);
+    }
+
+    /// Raw value of a subparam together with its source
+    pub fn sourced(&self, option: &str, subparam: &str) -> Option<(&ParamValue, &Source)> {
+        let key = self.option_key(option)?;
+        let value = self.options.get(key)?.get(subparam)?;
+        return self.sources.get(key).and_then(|x| x.get(subparam)).map(|source| (value, source));
+    }
+
+    /// Effective values with their sources as aligned `name = value  # source` lines, e.g. for `--print-config`.
+    /// Options without values are listed as `true`.
+    pub fn effective_config(&self) -> Vec<String> {
+        let mut entries = Vec::new();
+        let mut options: Vec<&String> = self.options.keys().collect();
+        options.sort();
+        for option in options.into_iter() {
+            let values = &self.options[option];
+            if values.is_empty() {
+                let source = self.option_sources.get(option).map(|x| x.to_string()).unwrap_or_default();
+                entries.push((option.clone(), String::from("true"), source));
+            }
+            let mut keys: Vec<&String> = values.keys().collect();
+            keys.sort();
+            for key in keys.into_iter() {
+                let source = self.sources.get(option).and_then(|x| x.get(key)).or_else(|| self.option_sources.get(option));
+                entries.push((format!("{} {}", option, key), values[key].to_string(), source.map(|x| x.to_string()).unwrap_or_default()));
+            }
+        }
+        let mut positionals: Vec<&String> = self.positionals.keys().collect();
+        positionals.sort();
+        for name in positionals.into_iter() {
+            let source = self.positional_sources.get(name).map(|x| x.to_string()).unwrap_or_default();
+            entries.push((name.clone(), self.positionals[name].to_string(), source));
+        }
+        let name_width = entries.iter().map(|x| x.0.len()).max().unwrap_or(0);
+        let value_width = entries.iter().map(|x| x.1.len()).max().unwrap_or(0);
+        return entries.into_iter()
+            .map(|(name, value, source)| format!("{:name_width$} = {:value_width$}  # {}", name, value, source, name_width = name_width, value_width = value_width))
+            .collect();
+    }
+
+    /// Resolves short identifiers and names given without leading dashes to the option name
+    fn option_key<'a>(&'a self, option: &'a str) -> Option<&'a str> {
+        if self.options.contains_key(option) {
+            return Some(option);
+        }
+        if let Some(name) = self.aliases.get(option) {
+            if self.options.contains_key(name) {
+                return Some(name);
+            }
+        }
+        return self.options.keys()
+            .find(|x| x.trim_start_matches('-') == option)
+            .map(|x| x.as_str());
+    }
+
+    fn convert<T>(val: &ParamValue, name: String) -> Result<T, ClitcError>
+        where T: for<'a> TryFrom<&'a ParamValue, Error = ClitcError>
+    {
+        return T::try_from(val).map_err(|e| match e {
+            ClitcError::TypeMismatch { expected, found, .. } => ClitcError::TypeMismatch { name, expected, found },
+            other => other,
+        });
+    }
+
+    /// Checks whether the option was given, by name (with or without dashes) or short identifier
+    pub fn is_present(&self, option: &str) -> bool {
+        return self.option_key(option).is_some();
+    }
+
+    /// Number of times the option was given
+    pub fn count(&self, option: &str) -> usize {
+        return self.option_key(option).and_then(|x| self.counts.get(x)).cloned().unwrap_or(0);
+    }
+
+    /// Raw subparam values of every occurrence of an option.
+    /// Options without `multiple` only keep their last occurrence.
+    pub fn occurrences(&self, option: &str) -> Vec<&HashMap<String, ParamValue>> {
+        let key = match self.option_key(option) {
+            Some(key) => key,
+            None => return Vec::new(),
+        };
+        match self.occurrences.get(key) {
+            Some(occurrences) => occurrences.iter().collect(),
+            None => self.options.get(key).into_iter().collect(),
+        }
+    }
+
+    /// Raw subparam values of an option
+    pub fn option(&self, option: &str) -> Option<&HashMap<String, ParamValue>> {
+        return self.option_key(option).and_then(|x| self.options.get(x));
+    }
+
+    /// Typed value of a subparam, e.g. `get::<i64>("lifetime", "secs")`.
+    /// Fails if the requested type does not match the declared type of the subparam.
+    pub fn get<T>(&self, option: &str, subparam: &str) -> Result<Option<T>, ClitcError>
+        where T: for<'a> TryFrom<&'a ParamValue, Error = ClitcError>
+    {
+        match self.option(option).and_then(|x| x.get(subparam)) {
+            Some(val) => Ok(Some(ParseResult::convert(val, format!("{} `{}`", option, subparam))?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Payload of a subparam of a custom type, e.g. `custom::<Version>("--min", "version")`
+    pub fn custom<T: Any>(&self, option: &str, subparam: &str) -> Result<Option<&T>, ClitcError> {
+        match self.option(option).and_then(|x| x.get(subparam)) {
+            Some(val) => match val.downcast_ref() {
+                Some(payload) => Ok(Some(payload)),
+                None => Err(ClitcError::TypeMismatch {
+                    name: format!("{} `{}`", option, subparam),
+                    expected: std::any::type_name::<T>().to_string(),
+                    found: val.type_name().to_string(),
+                }),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Typed value of a positional
+    pub fn positional<T>(&self, name: &str) -> Result<Option<T>, ClitcError>
+        where T: for<'a> TryFrom<&'a ParamValue, Error = ClitcError>
+    {
+        match self.positionals.get(name) {
+            Some(val) => Ok(Some(ParseResult::convert(val, format!("`{}`", name))?)),
+            None => Ok(None),
+        }
+    }
+
+    /// All values of a subparam across all occurrences as typed list; the elements of arrays are converted one by one
+    pub fn values_of<T>(&self, option: &str, subparam: &str) -> Result<Vec<T>, ClitcError>
+        where T: for<'a> TryFrom<&'a ParamValue, Error = ClitcError>
+    {
+        let name = format!("{} `{}`", option, subparam);
+        let mut ret = Vec::new();
+        for values in self.occurrences(option).into_iter() {
+            match values.get(subparam) {
+                Some(ParamValue::Array(vec)) => {
+                    for x in vec.iter() {
+                        ret.push(ParseResult::convert(&ParamValue::String(x.clone()), name.clone())?);
+                    }
+                },
+                Some(val) => ret.push(ParseResult::convert(val, name.clone())?),
+                None => {},
+            }
+        }
+        return Ok(ret);
+    }
+}
+
+/// Set of options and positionals, which can be nested as subcommands.
+/// The root of a spec is a command without a name.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Command {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) descr: Option<String>,
+    #[serde(default, rename = "options")]
+    params: Vec<Param>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    positionals: Vec<Positional>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    subcommands: Vec<Command>,
+    /// Accepts unambiguous prefixes of long option names, e.g. `--life` for `--lifetime`
+    #[serde(default, skip_serializing_if = "is_false")]
+    abbreviations: bool,
+}
+
+impl Command {
+    pub fn new(name: &str) -> Command {
+        return Command {
+            name: name.to_string(),
+            descr: None,
+            params: Vec::new(),
+            positionals: Vec::new(),
+            subcommands: Vec::new(),
+            abbreviations: false,
+        };
+    }
+
+    pub fn descr(mut self, descr: &str) -> Command {
+        self.descr = Some(descr.to_string());
+        return self;
+    }
+
+    pub fn option(mut self, param: Param) -> Command {
+        self.params.push(param);
+        return self;
+    }
+
+    /// Adds a positional, ordered after the previous ones unless it has an explicit ord
+    pub fn positional(mut self, mut positional: Positional) -> Command {
+        if positional.param.auto_ord {
+            positional.param.ord = self.positionals.len() as u8;
+        }
+        self.positionals.push(positional);
+        return self;
+    }
+
+    pub fn subcommand(mut self, subcommand: Command) -> Command {
+        self.subcommands.push(subcommand);
+        return self;
+    }
+
+    /// Enables abbreviated long options for this command, subcommands have their own setting
+    pub fn abbreviations(mut self) -> Command {
+        self.abbreviations = true;
+        return self;
+    }
+
+    /// Checks the spec of this command and its subcommands, whether loaded or built in code
+    fn validate(&mut self) -> Result<(), ClitcError> {
+        for (i, param) in self.params.iter_mut().enumerate() {
+            param.validate().map_err(|e| e.within(&format!("options[{}]", i)))?;
+        }
+        self.check_identifiers()?;
+
+        let mut ord_set = HashSet::new();
+        for (i, positional) in self.positionals.iter_mut().enumerate() {
+            positional.validate().map_err(|e| e.within(&format!("positionals[{}]", i)))?;
+            if !ord_set.insert(positional.param.ord) {
+                // ord not unique!
+                return Err(ClitcError::DuplicateOrd { option: String::from("positionals"), ord: positional.param.ord });
+            }
+        }
+        self.positionals.sort_by_key(|x| x.param.ord);
+
+        for (i, subcommand) in self.subcommands.iter_mut().enumerate() {
+            if subcommand.name.is_empty() {
+                return Err(missing(&format!("subcommands[{}].name", i)));
+            }
+            subcommand.validate().map_err(|e| e.within(&format!("subcommands[{}]", i)))?;
+        }
+        return Ok(());
+    }
+
+    /// Sets the parser of all subparams of a custom type, including those of subcommands
+    fn set_parser(&mut self, type_name: &str, parser: &Arc<dyn ValueParser>) -> Result<(), ClitcError> {
+        for (i, param) in self.params.iter_mut().enumerate() {
+            param.set_parser(type_name, parser).map_err(|e| e.within(&format!("options[{}]", i)))?;
+        }
+        for (i, positional) in self.positionals.iter_mut().enumerate() {
+            if positional.param.ptype.is_custom(type_name) {
+                positional.param.set_parser(parser).map_err(|e| e.within(&format!("positionals[{}]", i)))?;
+            }
+        }
+        for (i, subcommand) in self.subcommands.iter_mut().enumerate() {
+            subcommand.set_parser(type_name, parser).map_err(|e| e.within(&format!("subcommands[{}]", i)))?;
+        }
+        return Ok(());
+    }
+
+    /// Fails for the first subparam of a custom type without a parser, e.g. a misspelled built-in type
+    fn check_types(&self) -> Result<(), ClitcError> {
+        let subparams = self.params.iter().flat_map(|x| x.params.iter()).chain(self.positionals.iter().map(|x| &x.param));
+        for subparam in subparams {
+            if let (SubParamType::Custom(name), None) = (&subparam.ptype, &subparam.parser) {
+                return Err(ClitcError::UnknownType { name: name.clone() });
+            }
+        }
+        for subcommand in self.subcommands.iter() {
+            subcommand.check_types()?;
+        }
+        return Ok(());
+    }
+
+    /// Shorts and names are matched alike, so they have to be unique across all options.
+    /// With abbreviations, a name must not be the prefix of another one, as none of its abbreviations could be told apart.
+    fn check_identifiers(&self) -> Result<(), ClitcError> {
+        let mut identifiers: Vec<(&str, String)> = Vec::new();
+        for (i, param) in self.params.iter().enumerate() {
+            let mut own = vec![(&param.name[..], format!("options[{}].name", i))];
+            if let Some(short) = &param.short {
+                own.insert(0, (&short[..], format!("options[{}].short", i)));
+            }
+            for (identifier, path) in own.into_iter() {
+                if let Some((_, other)) = identifiers.iter().find(|x| x.0 == identifier) {
+                    return Err(ClitcError::ConflictingIdentifier { path, identifier: identifier.to_string(), other: other.clone() });
+                }
+                identifiers.push((identifier, path));
+            }
+        }
+
+        if self.abbreviations {
+            for (prefix, path) in identifiers.iter().filter(|x| x.0.starts_with("--")) {
+                if let Some((identifier, other)) = identifiers.iter().find(|x| x.0.len() > prefix.len() && x.0.starts_with(prefix)) {
+                    return Err(ClitcError::AmbiguousPrefix {
+                        path: path.clone(),
+                        prefix: prefix.to_string(),
+                        identifier: identifier.to_string(),
+                        other: other.clone(),
+                    });
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Param> {
+        return self.params.iter();
+    }
+
+    pub fn positionals(&self) -> std::slice::Iter<'_, Positional> {
+        return self.positionals.iter();
+    }
+
+    pub fn subcommands(&self) -> std::slice::Iter<'_, Command> {
+        return self.subcommands.iter();
+    }
+
+    /// Help lines for this command and all of its subcommands, keyed by command path
+    pub fn info(&self, path: &str, text: &mut HashMap<String, Vec<String>>) {
+        for subcommand in self.subcommands.iter() {
+            let path = if path.is_empty() { subcommand.name.clone() } else { format!("{} {}", path, subcommand.name) };
+            let mut info = vec![format!("\t{}\t{}", path, subcommand.descr.clone().unwrap_or_default())];
+            for param in subcommand.params.iter() {
+                info.extend(param.info().into_iter().map(|x| format!("\t{}", x)));
+            }
+            text.insert(path.clone(), info);
+            subcommand.info(&path, text);
+        }
+    }
+
+    /// Whether an option or subcommand uses the identifier, which then is not taken by the built-in help
+    fn defines(&self, identifier: &str) -> bool {
+        return self.params.iter().any(|x| x.short.as_deref() == Some(identifier) || x.name == identifier)
+            || self.subcommands.iter().any(|x| x.name == identifier);
+    }
+
+    /// Topic of a leading `help`, if the command has no positionals which could take it as value
+    fn help_command(&self, args: &[(usize, String)]) -> Option<Vec<String>> {
+        let (_, first) = args.first()?;
+        if first != "help" || !self.positionals.is_empty() || self.defines("help") {
+            return None;
+        }
+        // Arguments after `--` are not part of the topic
+        return Some(args.iter().skip(1).take_while(|x| x.1 != "--").map(|x| x.1.clone()).collect());
+    }
+
+    /// Whether the token is `--help` or `-h` and not used by an option of the spec
+    fn is_help_flag(&self, token: &str) -> bool {
+        return (token == "--help" || token == "-h") && !self.defines(token);
+    }
+
+    /// Splits the arguments at the subcommand names, which may be preceded by options of the commands on the way.
+    /// Returns every command of the path with the arguments up to the next name, the innermost one with the rest of them.
+    /// Subcommands inherit the options of their parents.
+    fn resolve(&self, mut args: Vec<(usize, String)>, path: &mut Vec<String>, style: TokenStyle) -> Result<Vec<Segment<'_>>, ClitcError> {
+        let mut segments = Vec::new();
+        let mut command = Cow::Borrowed(self);
+        let mut i = 0;
+        while i < args.len() && args[i].1 != "--" {
+            let subcommand = command.subcommands.iter().find(|x| x.name == args[i].1).map(|x| x.inherit(&command.params));
+            if let Some(subcommand) = subcommand {
+                path.push(subcommand.name.clone());
+                let rest = args.split_off(i + 1);
+                args.pop();
+                segments.push((std::mem::replace(&mut command, Cow::Owned(subcommand)), std::mem::replace(&mut args, rest)));
+                i = 0;
+                continue;
+            }
+            if style == TokenStyle::Gnu {
+                let expanded = command.expand_gnu(vec![args[i].clone()])?;
+                args.splice(i..i + 1, expanded);
+            }
+            match command.find_param(args[i].0, &args[i].1)? {
+                Some(param) => i += 1 + command.option_values(param, &args[i + 1..])?,
+                // Positionals and unknown arguments end the command path
+                None => break,
+            }
+        }
+        segments.push((command, args));
+        return Ok(segments);
+    }
+
+    /// Copy of the subcommand with the options of its parent.
+    /// Options of the subcommand shadow inherited ones of the same name, inherited shorts in use are dropped.
+    fn inherit(&self, parent: &[Param]) -> Command {
+        let mut command = self.clone();
+        for param in parent.iter() {
+            if command.defines(&param.name) {
+                continue;
+            }
+            let mut param = param.clone();
+            if param.short.as_deref().is_some_and(|x| command.defines(x)) {
+                param.short = None;
+            }
+            command.params.push(param);
+        }
+        return command;
+    }
+
+    /// Number of arguments following an option which are its values.
+    /// Values stop at the next option, arrays taking several arguments also at a subcommand name.
+    fn option_values(&self, param: &Param, args: &[(usize, String)]) -> Result<usize, ClitcError> {
+        let mut count = param.numeric_values(args);
+        for subparam in param.params.iter().skip(count) {
+            let max = subparam.get_arity().max;
+            let mut taken = 0;
+            while max.is_none_or(|x| taken < x) && count < args.len() {
+                let (index, arg) = &args[count];
+                let subcommand = max != Some(1) && self.subcommands.iter().any(|x| x.name == *arg);
+                if arg == "--" || subcommand || self.find_param(*index, arg)?.is_some() {
+                    break;
+                }
+                taken += 1;
+                count += 1;
+            }
+            if taken == 0 {
+                break;
+            }
+        }
+        return Ok(count);
+    }
+
+    /// Option matching the argument by short or name, or by an unambiguous prefix of its name if abbreviations are enabled
+    fn find_param(&self, index: usize, arg: &str) -> Result<Option<&Param>, ClitcError> {
+        if let Some(param) = self.params.iter().find(|x| x.short.as_deref() == Some(arg) || x.name == arg) {
+            return Ok(Some(param));
+        }
+        if !self.abbreviations || !arg.starts_with("--") || arg.len() <= 2 {
+            return Ok(None);
+        }
+        let candidates: Vec<&Param> = self.params.iter().filter(|x| x.name.starts_with(arg)).collect();
+        if candidates.len() > 1 {
+            return Err(ClitcError::AmbiguousOption {
+                token: arg.to_string(),
+                index,
+                candidates: candidates.iter().map(|x| x.name.clone()).collect(),
+            });
+        }
+        return Ok(candidates.first().cloned());
+    }
+
+    /// Splits GNU style arguments into separate tokens, which keep the index of the argument they came from
+    fn expand_gnu(&self, args: Vec<(usize, String)>) -> Result<Vec<(usize, String)>, ClitcError> {
+        let mut ret = Vec::new();
+        // Subparams of the last option still waiting for their values
+        let mut pending: &[SubParam] = &[];
+        for (index, arg) in args.into_iter() {
+            let tokens = match pending.first() {
+                // Negative numbers are values of int and num subparams, not clusters
+                Some(subparam) if subparam.is_number(&arg) => vec![arg],
+                _ => self.expand_token(index, arg)?,
+            };
+            for token in tokens.into_iter() {
+                let value = pending.first().is_some_and(|x| x.is_number(&token));
+                pending = match self.find_param(index, &token)? {
+                    Some(param) if !value => &param.params,
+                    _ => pending.get(1..).unwrap_or(&[]),
+                };
+                ret.push((index, token));
+            }
+        }
+        return Ok(ret);
+    }
+
+    /// Tokens of a single GNU style argument
+    fn expand_token(&self, index: usize, arg: String) -> Result<Vec<String>, ClitcError> {
+        if arg.starts_with("--") {
+            // --name=value
+            if let Some((name, value)) = arg.split_once('=') {
+                if let Some(param) = self.find_param(index, name)? {
+                    // A value would otherwise be left over for the positionals
+                    if param.params.is_empty() {
+                        return Err(ClitcError::WrongArity { name: param.name.clone(), expected: String::from("exactly 0"), found: 1 });
+                    }
+                    return Ok(vec![param.name.clone(), value.to_string()]);
+                }
+            }
+        } else if arg.starts_with('-') && arg.len() > 2 && self.find_param(index, &arg)?.is_none() {
+            // -abc or -n5
+            if let Some(tokens) = self.split_cluster(&arg) {
+                return Ok(tokens);
+            }
+        }
+        return Ok(vec![arg]);
+    }
+
+    /// Shorts clustered in one argument, the first one taking values ends the cluster with its attached value.
+    /// Returns `None` if any of the characters is not a short, so the argument stays as it is.
+    fn split_cluster(&self, arg: &str) -> Option<Vec<String>> {
+        let mut tokens = Vec::new();
+        for (i, c) in arg.char_indices().skip(1) {
+            let short = format!("-{}", c);
+            let param = self.params.iter().find(|x| x.short.as_deref() == Some(&short[..]))?;
+            tokens.push(short);
+            if !param.params.is_empty() {
+                let value = &arg[i + c.len_utf8()..];
+                if !value.is_empty() {
+                    tokens.push(value.to_string());
+                }
+                break;
+            }
+        }
+        return Some(tokens);
+    }
+
+    /// Position of the next option in `args`, following the arguments of `param`
+    fn next_option(&self, param: &Param, args: &[(usize, String)]) -> Result<usize, ClitcError> {
+        let skip = param.numeric_values(args);
+        for (i, (index, arg)) in args.iter().enumerate().skip(skip) {
+            if self.find_param(*index, arg)?.is_some() {
+                return Ok(i);
+            }
+        }
+        return Ok(args.len());
+    }
+
+    fn get_named_locations(&self, args: &[(usize, String)]) -> Result<Vec<(usize, &Param)>, ClitcError> {
+        let mut locations: Vec<(usize, &Param)> = Vec::new();
+        let mut i = 0;
+        while i < args.len() {
+            let (index, arg) = &args[i];
+            // Param matched to argument:
+            if let Some(matching) = self.find_param(*index, arg)? {
+                locations.push((i, matching));
+                // Skip numbers for its subparams, even if they look like an option
+                i += matching.numeric_values(&args[i + 1..]);
+            }
+            i += 1;
+        }
+        return Ok(locations);
+    }
+
+    fn process_locations(&self, locations: Vec<(usize, &Param)>, args: &mut Vec<(usize, String)>, ret: &mut ParseResult, leftovers: &mut Vec<(usize, String)>) -> Result<(), ClitcError> {
+        // Arguments in front of the first named param belong to no param
+        let first = locations.first().map(|x| x.0).unwrap_or(args.len());
+        leftovers.extend(args.drain(..first));
+        for i in 0..locations.len() {
+            let (index, param) = locations[i];
+            let mut interval;
+            if (i + 1) < locations.len() {
+                let next_index = locations[i + 1].0;
+                interval = next_index - index;
+            } else {
+                interval = args.len();
+            }
+            // Leave out named identifier argument
+            let (arg_index, _) = args.remove(0);
+            interval -= 1;
+            // Parse Subparams
+            let mut sub_args = args.drain(..interval).collect();
+            let res = param.match_indexed(&mut sub_args)?;
+            ret.insert_option(param, arg_index, res);
+            // Arguments not consumed by the subparams are left over
+            leftovers.append(&mut sub_args);
+        }
+        return Ok(());
+    }
+
+    fn process_sequentially(&self, args: &mut Vec<(usize, String)>, ret: &mut ParseResult, leftovers: &mut Vec<(usize, String)>) -> Result<(), ClitcError> {
+        while !args.is_empty() {
+            // Iterate through arguments to find match with params
+            let mut res = None;
+            for (i, (index, arg)) in args.iter().enumerate() {
+                if let Some(param) = self.find_param(*index, arg)? {
+                    // Current argument index and matched param
+                    res = Some((i, param));
+                    break;
+                }
+            }
+            // Check if match was found
+            if let Some((index, matching)) = res {
+                // Cut off arguments to current location
+                leftovers.extend(args.drain(..index));
+                let (arg_index, _) = args.remove(0);
+                // Subparams only take the arguments up to the next option
+                let end = self.next_option(matching, args)?;
+                let mut sub_args = args.drain(..end).collect();
+                let result = matching.match_indexed(&mut sub_args)?;
+                ret.insert_option(matching, arg_index, result);
+                leftovers.append(&mut sub_args);
+
+            } else {
+                // Abort if no more can be found
+                leftovers.append(args);
+                break;
+            }
+        }
+        return Ok(());
+    }
+
+    fn process_positionals(&self, leftovers: &mut Vec<(usize, String)>, ret: &mut ParseResult) -> Result<(), ClitcError> {
+        for positional in self.positionals.iter() {
+            let index = leftovers.first().map(|x| x.0).unwrap_or(0);
+            if let Some((key, val)) = positional.match_with(leftovers)? {
+                ret.fill_positional(key, val, Source::Cli(index));
+            }
+        }
+        return Ok(());
+    }
+
+    /// Parses options from `args`, returns the arguments not used by them
+    fn parse_options(&self, mut args: Vec<(usize, String)>, sequential: bool, ret: &mut ParseResult) -> Result<Vec<(usize, String)>, ClitcError> {
+        let mut leftovers = Vec::new();
+        for param in self.params.iter() {
+            if let Some(short) = param.short.clone() {
+                ret.aliases.insert(short, param.name.clone());
+            }
+        }
+
+        if sequential {
+            self.process_sequentially(&mut args, ret, &mut leftovers)?;
+        } else {
+            // Get param locations
+            let locations = self.get_named_locations(&args)?;
+
+            // Process named parameters
+            self.process_locations(locations, &mut args, ret, &mut leftovers)?;
+        }
+        return Ok(leftovers);
+    }
+
+    /// Parses options from `args`, `escaped` arguments after `--` are only used for positionals.
+    /// With `help`, returns true as soon as `--help` or `-h` is left over by the options, i.e. it was not taken as a value.
+    fn parse_indexed(&self, args: Vec<(usize, String)>, escaped: Vec<(usize, String)>, sequential: bool, help: bool, ret: &mut ParseResult) -> Result<bool, ClitcError> {
+        let mut leftovers = self.parse_options(args, sequential, ret)?;
+        if help && leftovers.iter().any(|x| self.is_help_flag(&x.1)) {
+            return Ok(true);
+        }
+        leftovers.extend(escaped);
+        self.process_positionals(&mut leftovers, ret)?;
+        ret.unmatched.extend(leftovers.into_iter().map(|(_, x)| x));
+        return Ok(false);
+    }
+
+    /// Fills options and subparams not given as arguments from their environment variables
+    fn fill_env(&self, ret: &mut ParseResult, lookup: &EnvLookup) -> Result<(), ClitcError> {
+        for param in self.params.iter() {
+            if let (Some(var), false) = (&param.env, ret.options.contains_key(&param.name)) {
+                if let Some(value) = lookup(var) {
+                    let source = Source::Env(var.clone());
+                    if param.params.is_empty() {
+                        // Counted flags take the count, other flags are set by any truthy value
+                        let count = value.trim().parse::<i64>().unwrap_or(if is_truthy(&value) { 1 } else { 0 });
+                        ret.insert_flag(param, count, source);
+                    } else {
+                        let mut tokens = value.split_whitespace().map(|x| (0, x.to_string())).collect();
+                        let values = param.match_with(&mut tokens).map_err(|e| e.from_env(var))?;
+                        if !tokens.is_empty() {
+                            return Err(ClitcError::InvalidEnv { var: var.clone(), reason: String::from("has too many values") });
+                        }
+                        ret.insert_values(param, values, source);
+                    }
+                }
+            }
+            for subparam in param.params.iter() {
+                if let Some(var) = &subparam.env {
+                    if ret.has_value(&param.name, &subparam.get_name()) {
+                        continue;
+                    }
+                    if let Some(value) = lookup(var) {
+                        let (key, val) = subparam.match_env(&param.name, var, value)?;
+                        ret.fill_value(&param.name, key, val, Source::Env(var.clone()));
+                    }
+                }
+            }
+        }
+        for positional in self.positionals.iter() {
+            if let Some(var) = &positional.param.env {
+                if ret.positionals.contains_key(&positional.get_name()) {
+                    continue;
+                }
+                if let Some(value) = lookup(var) {
+                    let (key, val) = positional.param.match_env("positional", var, value)?;
+                    ret.fill_positional(key, val, Source::Env(var.clone()));
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    /// Fills options, subparams and positionals not set yet from a table of a config file.
+    /// Options are keyed by their name without leading dashes, tables of subcommands by the subcommand name.
+    fn fill_config(&self, ret: &mut ParseResult, config: &ConfigFile, table: &Map<String, Value>, path: &str) -> Result<(), ClitcError> {
+        for (name, value) in table.iter() {
+            let key = if path.is_empty() { name.clone() } else { format!("{}.{}", path, name) };
+            let source = Source::Config { file: config.path.clone(), key: key.clone() };
+            if let Some(param) = self.params.iter().find(|x| x.name.trim_start_matches('-') == name) {
+                if param.params.is_empty() {
+                    // Flags are set by `true` or their count
+                    let count = match value {
+                        Value::Bool(set) => *set as i64,
+                        Value::Number(count) if count.is_u64() => count.as_u64().unwrap_or(0) as i64,
+                        _ => return Err(config.invalid(&key, "expected a boolean or a count")),
+                    };
+                    ret.insert_flag(param, count, source);
+                } else if param.multiple == Multiple::Collect {
+                    // Every item of a list is an occurrence
+                    let items = match value {
+                        Value::Array(items) => items.iter().enumerate().map(|(i, x)| (format!("{}[{}]", key, i), x)).collect(),
+                        value => vec![(key.clone(), value)],
+                    };
+                    let mut occurrences = Vec::new();
+                    for (key, item) in items.into_iter() {
+                        let values = param.match_config(config, &key, item)?;
+                        occurrences.push(values.into_iter().map(|(subname, val, _)| (subname, val)).collect());
+                    }
+                    ret.insert_occurrences(param, occurrences, source);
+                } else {
+                    for (subname, val, key) in param.match_config(config, &key, value)?.into_iter() {
+                        ret.fill_value(&param.name, subname, val, Source::Config { file: config.path.clone(), key });
+                    }
+                }
+            } else if let Some(positional) = self.positionals.iter().find(|x| x.get_name() == *name) {
+                let (name, val) = positional.param.match_config("positional", config, &key, value)?;
+                ret.fill_positional(name, val, source);
+            } else if !(value.is_object() && self.subcommands.iter().any(|x| x.name == *name)) {
+                return Err(config.invalid(&key, "is not a known option or positional"));
+            }
+        }
+        return Ok(());
+    }
+
+    /// Fills the defaults of subparams of given options and of positionals
+    fn fill_defaults(&self, ret: &mut ParseResult) {
+        for param in self.params.iter() {
+            if !ret.options.contains_key(&param.name) {
+                continue;
+            }
+            for subparam in param.params.iter() {
+                if let Some(default) = &subparam.default {
+                    ret.fill_value(&param.name, subparam.get_name(), default.clone(), Source::Default);
+                }
+            }
+        }
+        for positional in self.positionals.iter() {
+            if let Some(default) = &positional.param.default {
+                ret.fill_positional(positional.get_name(), default.clone(), Source::Default);
+            }
+        }
+    }
+
+    /// Checks parsed values for required options and subparams, reporting all missing items at once
+    fn check_required(&self, ret: &ParseResult) -> Result<(), ClitcError> {
+        let mut missing = Vec::new();
+        for param in self.params.iter() {
+            param.missing_required(ret.options.get(&param.name), &mut missing);
+        }
+        for positional in self.positionals.iter() {
+            let name = positional.get_name();
+            if positional.param.required && !ret.positionals.contains_key(&name) {
+                missing.push((name, None));
+            }
+        }
+        if !missing.is_empty() {
+            return Err(ClitcError::MissingRequired { missing });
+        }
+        return Ok(());
+    }
+}
+
+/// Source of values merged by `CliParameters::parse_layered`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Layer {
+    /// Program arguments
+    Cli,
+    /// Environment variables of options and subparams with `env`
+    Env,
+    /// Config file
+    Config,
+}
+
+/// Values of a config file, validated against the spec when parsing.
+/// Keys are option names without leading dashes, positional names and subcommand names holding tables of their own, e.g. for TOML:
+///
+/// ```toml
+/// verbose = true
+/// lifetime = { secs = 30, expected_val = 2.5 }
+///
+/// [serve]
+/// port = 8080
+/// ```
+pub struct ConfigFile {
+    path: String,
+    data: Map<String, Value>,
+}
+
+impl ConfigFile {
+    /// Config from already parsed data, `path` names it in errors and sources
+    pub fn from_value(path: &str, data: Value) -> Result<ConfigFile, ClitcError> {
+        let data = match data {
+            Value::Object(data) => data,
+            _ => return Err(ClitcError::InvalidConfig {
+                file: path.to_string(),
+                key: String::new(),
+                reason: String::from("expected a table of options"),
+            }),
+        };
+        return Ok(ConfigFile { path: path.to_string(), data });
+    }
+
+    /// Loads a config file, detecting its format by the file extension
+    pub fn from_path<P>(path: P) -> Result<ConfigFile, ClitcError>
+        where P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let name = path.display().to_string();
+        let invalid = |reason: String| ClitcError::InvalidConfig { file: name.clone(), key: String::new(), reason };
+        let extension = path.extension().and_then(|x| x.to_str()).unwrap_or("");
+        let data = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let data: Value = match extension {
+            "json" => serde_json::from_str(&data).map_err(|e| invalid(e.to_string()))?,
+            #[cfg(feature = "toml")]
+            "toml" => toml::from_str(&data).map_err(|e| invalid(e.to_string()))?,
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => serde_yaml::from_str(&data).map_err(|e| invalid(e.to_string()))?,
+            _ => return Err(invalid(format!("unsupported config format `{}`", extension))),
+        };
+        return ConfigFile::from_value(&name, data);
+    }
+
+    /// Table of the given (nested) subcommand
+    fn table(&self, command: &[String]) -> Option<&Map<String, Value>> {
+        let mut table = &self.data;
+        for name in command.iter() {
+            table = table.get(name)?.as_object()?;
+        }
+        return Some(table);
+    }
+
+    /// Tokens of a value, as if they were given as arguments
+    fn tokens(&self, key: &str, value: &Value) -> Result<Vec<(usize, String)>, ClitcError> {
+        let items = match value {
+            Value::Array(items) => items.iter().collect(),
+            value => vec![value],
+        };
+        let mut tokens = Vec::new();
+        for item in items.into_iter() {
+            let token = match item {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                _ => return Err(self.invalid(key, "expected a string, number, boolean or a list of them")),
+            };
+            tokens.push((0, token));
+        }
+        return Ok(tokens);
+    }
+
+    fn invalid(&self, key: &str, reason: &str) -> ClitcError {
+        return ClitcError::InvalidConfig { file: self.path.clone(), key: key.to_string(), reason: reason.to_string() };
+    }
+}
+
+/// How arguments are split into option identifiers and values before matching
+#[derive(Clone, Copy, PartialEq)]
+pub enum TokenStyle {
+    /// Every option identifier has to be a token of its own
+    Strict,
+    /// Additionally expands `--name=value`, clustered flags `-abc` and attached values `-n5`
+    Gnu,
+}
+
+pub struct CliParameters {
+    root: Command,
+    sequential: bool,
+    style: TokenStyle,
+    /// Parsers of custom subparam types, keyed by type name
+    parsers: HashMap<String, Arc<dyn ValueParser>>,
+    /// Formatter of the built-in help, which is disabled without one
+    help: Option<HelpFormatter>,
+}
+
+/// Builds `CliParameters` in code, e.g.
+/// `CliParameters::builder().option(Param::new("--lifetime").short("-l").arg(SubParam::int("secs"))).build()`
+pub struct CliParametersBuilder {
+    root: Command,
+    parsers: HashMap<String, Arc<dyn ValueParser>>,
+}
+
+impl CliParametersBuilder {
+    pub fn option(mut self, param: Param) -> CliParametersBuilder {
+        self.root = self.root.option(param);
+        return self;
+    }
+
+    pub fn positional(mut self, positional: Positional) -> CliParametersBuilder {
+        self.root = self.root.positional(positional);
+        return self;
+    }
+
+    pub fn subcommand(mut self, subcommand: Command) -> CliParametersBuilder {
+        self.root = self.root.subcommand(subcommand);
+        return self;
+    }
+
+    pub fn abbreviations(mut self) -> CliParametersBuilder {
+        self.root = self.root.abbreviations();
+        return self;
+    }
+
+    /// Parser for subparams of a custom type, see `CliParameters::register_parser`
+    pub fn parser<P>(mut self, type_name: &str, parser: P) -> CliParametersBuilder
+        where P: ValueParser + 'static
+    {
+        self.parsers.insert(type_name.to_string(), Arc::new(parser));
+        return self;
+    }
+
+    /// Runs the same validation as the spec loaders
+    pub fn build(self) -> Result<CliParameters, ClitcError> {
+        let mut cli_params = CliParameters::from(self.root)?;
+        for (type_name, parser) in self.parsers.into_iter() {
+            cli_params.set_parser(&type_name, parser)?;
+        }
+        cli_params.check_types()?;
+        return Ok(cli_params);
+    }
+}
+
+impl CliParameters {
+    fn from(mut root: Command) -> Result<CliParameters, ClitcError> {
+        root.validate()?;
+        return Ok(CliParameters{root, sequential: false, style: TokenStyle::Strict, parsers: HashMap::new(), help: None});
+    }
+
+    pub fn builder() -> CliParametersBuilder {
+        return CliParametersBuilder { root: Command::new(""), parsers: HashMap::new() };
+    }
+
+    /// Builder starting from this spec, so it can be changed at runtime
+    pub fn to_builder(&self) -> CliParametersBuilder {
+        return CliParametersBuilder { root: self.root.clone(), parsers: self.parsers.clone() };
+    }
+
+    /// Registers the parser for subparams of a custom `"type"`, e.g. `"semver"`.
+    /// Their tokens are parsed into `ParamValue::Custom`; defaults are converted right away.
+    pub fn register_parser<P>(&mut self, type_name: &str, parser: P) -> Result<(), ClitcError>
+        where P: ValueParser + 'static
+    {
+        return self.set_parser(type_name, Arc::new(parser));
+    }
+
+    /// Checks that every custom type of the spec has a registered parser.
+    /// Loaders accept any type name, since parsers are registered afterwards, so this runs before every parse as well.
+    pub fn check_types(&self) -> Result<(), ClitcError> {
+        return self.root.check_types();
+    }
+
+    fn set_parser(&mut self, type_name: &str, parser: Arc<dyn ValueParser>) -> Result<(), ClitcError> {
+        // Names of built-in types are not read as custom types, so their parser would never be used
+        if serde_json::from_value::<SubParamType>(Value::from(type_name)).map(|x| !x.is_custom(type_name)).unwrap_or(true) {
+            return Err(ClitcError::WrongFormat {
+                path: type_name.to_string(),
+                reason: String::from("built-in types cannot have a custom parser"),
+            });
+        }
+        self.root.set_parser(type_name, &parser)?;
+        self.parsers.insert(type_name.to_string(), parser);
+        return Ok(());
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(data: &str) -> Result<CliParameters, ClitcError> {
+        let root: Command = serde_json::from_str(data)?;
+        CliParameters::from(root)
+    }
+
+    pub fn from_slice(data: &[u8]) -> Result<CliParameters, ClitcError> {
+        let root: Command = serde_json::from_slice(data)?;
+        CliParameters::from(root)
+    }
+
+    pub fn from_reader<R>(reader: R) -> Result<CliParameters, ClitcError>
+        where R: std::io::Read,
+    {
+        let root: Command = serde_json::from_reader(reader)?;
+        CliParameters::from(root)
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(data: &str) -> Result<CliParameters, ClitcError> {
+        let root: Command = toml::from_str(data)?;
+        CliParameters::from(root)
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_str(data: &str) -> Result<CliParameters, ClitcError> {
+        let root: Command = serde_yaml::from_str(data)?;
+        CliParameters::from(root)
+    }
+
+    /// Loads a spec file, detecting its format by the file extension
+    pub fn from_path<P>(path: P) -> Result<CliParameters, ClitcError>
+        where P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|x| x.to_str()).unwrap_or("");
+        let data = std::fs::read_to_string(path)?;
+        match extension {
+            "json" => CliParameters::from_str(&data),
+            #[cfg(feature = "toml")]
+            "toml" => CliParameters::from_toml_str(&data),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => CliParameters::from_yaml_str(&data),
+            _ => Err(ClitcError::WrongFormat {
+                path: path.display().to_string(),
+                reason: format!("unsupported spec format `{}`", extension),
+            }),
+        }
+    }
+
+    /// Writes the spec back out in the format read by `from_str`
+    pub fn to_json(&self) -> Result<String, ClitcError> {
+        return Ok(serde_json::to_string_pretty(&self.root)?);
+    }
+
+    pub fn set_sequential_processing(&mut self, seq: bool) {
+        self.sequential = seq;
+    }
+
+    pub fn set_token_style(&mut self, style: TokenStyle) {
+        self.style = style;
+    }
+
+    /// Formatter of the built-in `--help`, `-h` and `help <topic>`, `None` turns them off
+    pub fn set_help(&mut self, help: Option<HelpFormatter>) {
+        self.help = help;
+    }
+
+    /// Turns on the built-in help, showing the name of the root command or the file name of the program
+    pub fn enable_help(&mut self) {
+        let program = match self.root.name.is_empty() {
+            true => std::env::args().next().as_deref().map(Path::new).and_then(|x| x.file_name()).map(|x| x.to_string_lossy().to_string()).unwrap_or_default(),
+            false => self.root.name.clone(),
+        };
+        self.help = Some(HelpFormatter::new(&program));
+    }
+
+    /// Help page of the command at `path` as `ClitcError::HelpRequested`
+    fn help_requested(&self, help: &HelpFormatter, path: Vec<String>) -> ClitcError {
+        return match help.render(self, &path) {
+            Some(help) => ClitcError::HelpRequested { help },
+            None => ClitcError::UnknownCommand { tokens: path },
+        };
+    }
+
+    pub fn help(&self) -> Option<&HelpFormatter> {
+        return self.help.as_ref();
+    }
+
+    pub(crate) fn root(&self) -> &Command {
+        return &self.root;
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Param> {
+        return self.root.iter();
+    }
+
+    pub fn positionals(&self) -> std::slice::Iter<'_, Positional> {
+        return self.root.positionals();
+    }
+
+    pub fn subcommands(&self) -> std::slice::Iter<'_, Command> {
+        return self.root.subcommands();
+    }
+
+    /// Help lines for every subcommand, keyed by command path
+    pub fn subcommand_info(&self) -> HashMap<String, Vec<String>> {
+        let mut text = HashMap::new();
+        self.root.info("", &mut text);
+        return text;
+    }
+
+    /// Parses arguments and merges them with the other layers, earlier layers in `precedence` win over later ones and defaults
+    fn parse_indexed(&self, args: Vec<(usize, String)>, precedence: &[Layer], config: Option<&ConfigFile>) -> Result<ParseResult, ClitcError> {
+        self.check_types()?;
+        let mut cli = ParseResult::default();
+        let mut segments = self.root.resolve(args, &mut cli.command, self.style)?;
+        // Positionals and the rest of the arguments belong to the innermost subcommand, options before its name to the parents
+        let (command, mut args) = segments.pop().unwrap();
+        for (parent, args) in segments.into_iter() {
+            let leftovers = parent.parse_options(args, self.sequential, &mut cli)?;
+            cli.unmatched.extend(leftovers.into_iter().map(|(_, x)| x));
+        }
+        if let (Some(help), Some(mut topic)) = (&self.help, command.help_command(&args)) {
+            let mut path = cli.command.clone();
+            path.append(&mut topic);
+            return Err(self.help_requested(help, path));
+        }
+        // Everything after the `--` marker is positional
+        let escaped = match args.iter().position(|x| x.1 == "--") {
+            Some(marker) => args.split_off(marker).into_iter().skip(1).collect(),
+            None => Vec::new(),
+        };
+        if self.style == TokenStyle::Gnu {
+            args = command.expand_gnu(args)?;
+        }
+        if command.parse_indexed(args, escaped, self.sequential, self.help.is_some(), &mut cli)? {
+            if let Some(help) = &self.help {
+                return Err(self.help_requested(help, cli.command));
+            }
+        }
+
+        let mut ret = ParseResult {
+            command: cli.command.clone(),
+            unmatched: cli.unmatched.clone(),
+            aliases: cli.aliases.clone(),
+            ..ParseResult::default()
+        };
+        let mut cli = Some(cli);
+        for layer in precedence.iter() {
+            match layer {
+                Layer::Cli => if let Some(cli) = cli.take() {
+                    ret.merge(cli);
+                },
+                Layer::Env => command.fill_env(&mut ret, &|var| std::env::var(var).ok())?,
+                Layer::Config => if let Some(config) = config {
+                    if let Some(table) = config.table(&ret.command) {
+                        let path = ret.command.join(".");
+                        command.fill_config(&mut ret, config, table, &path)?;
+                    }
+                },
+            }
+        }
+        command.fill_defaults(&mut ret);
+        command.check_required(&ret)?;
+        return Ok(ret);
+    }
+
+    pub fn parse_vec(&self, args: Vec<String>) -> Result<ParseResult, ClitcError> {
+        return self.parse_indexed(args.into_iter().enumerate().collect(), &[Layer::Cli], None);
+    }
+
+    /// Parses the arguments of the program, options and subparams with `env` fall back to their environment variables
+    pub fn parse_args(&self) -> Result<ParseResult, ClitcError> {
+        // Skip program path cl argument, but keep indices relative to it
+        let args: Vec<(usize, String)> = std::env::args().enumerate().skip(1).collect();
+
+        return self.parse_indexed(args, &[Layer::Cli, Layer::Env], None);
+    }
+
+    /// Parses the arguments of the program merged with the environment and a config file, validating all of them against the spec.
+    /// Layers earlier in `precedence` win over later ones, defaults are used last, e.g. `&[Layer::Cli, Layer::Env, Layer::Config]`.
+    pub fn parse_layered(&self, config: Option<&ConfigFile>, precedence: &[Layer]) -> Result<ParseResult, ClitcError> {
+        let args: Vec<(usize, String)> = std::env::args().enumerate().skip(1).collect();
+
+        return self.parse_indexed(args, precedence, config);
+    }
+
+    /// Parses the given arguments merged with the environment and a config file, like `parse_layered`
+    pub fn parse_vec_layered(&self, args: Vec<String>, config: Option<&ConfigFile>, precedence: &[Layer]) -> Result<ParseResult, ClitcError> {
+        return self.parse_indexed(args.into_iter().enumerate().collect(), precedence, config);
+    }
+
+    pub fn parse_str<'a, F>(&self, data: &'a str, split: F) -> Result<ParseResult, ClitcError>
+        where F: Fn(&'a str) -> Vec<String>
+    {
+        let args: Vec<String> = split(data);
+        self.parse_vec(args)
+    }
+
+    pub fn parse_str_whitespace(&self, data: &str) -> Result<ParseResult, ClitcError> {
+        self.parse_str(data, |args| args.split_whitespace().map(|x| x.to_string()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(subparam: &SubParam, raw: &str) -> Result<ParamValue, ClitcError> {
+        let mut args = vec![(1, raw.to_string())];
+        return subparam.match_with("--opt", &mut args).map(|(_, val)| val);
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("5s"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_duration("1d2s500ms"), Some(Duration::from_millis((24 * 60 * 60 + 2) * 1000 + 500)));
+        for raw in ["", "1m5", "5x", "m", "1.5s", "-5s", "1 m"].iter() {
+            assert_eq!(parse_duration(raw), None, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn durations_overflow() {
+        assert_eq!(parse_duration(&format!("{}h", u64::MAX / 60)), None);
+        assert_eq!(parse_duration(&format!("{}s{}s", u64::MAX, 1)), None);
+        assert_eq!(parse_duration("99999999999999999999s"), None);
+        assert_eq!(parse_duration(&format!("{}", u64::MAX)), Some(Duration::from_secs(u64::MAX)));
+    }
+
+    #[test]
+    fn formatted_durations() {
+        for (secs, formatted) in [(0, "0s"), (45, "45s"), (120, "2m"), (5400, "90m"), (7200, "2h"), (90061, "90061s"), (2 * 24 * 60 * 60, "2d")].iter() {
+            assert_eq!(format_duration(&Duration::from_secs(*secs)), *formatted);
+            assert_eq!(parse_duration(formatted), Some(Duration::from_secs(*secs)));
+        }
+        assert_eq!(format_duration(&Duration::from_millis(1500)), "1500ms");
+        assert_eq!(parse_duration("1500ms"), Some(Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn durations_round_trip_through_to_json() {
+        let spec = r#"{ "options": [ { "name": "--timeout", "params": [ { "ord": 0, "name": "after", "type": "duration", "default": "1h30m" } ] } ] }"#;
+        let written = CliParameters::from_str(spec).unwrap().to_json().unwrap();
+        assert!(written.contains("\"90m\""), "{}", written);
+        let cli_params = CliParameters::from_str(&written).unwrap();
+        let res = cli_params.parse_vec(vec![String::from("--timeout")]).unwrap();
+        assert_eq!(res.get::<Duration>("--timeout", "after").unwrap(), Some(Duration::from_secs(90 * 60)));
+    }
+
+    #[test]
+    fn urls() {
+        for raw in ["https://example.com", "http://localhost:8080/a?b=c", "git+ssh://host/repo", "file:///tmp/x"].iter() {
+            assert!(is_url(raw), "{}", raw);
+        }
+        for raw in ["example.com", "https://", "http:///path", "1http://host", "ht tp://host", "https://a b", "://host"].iter() {
+            assert!(!is_url(raw), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn values_of_each_type() {
+        assert!(matches!(parse(&SubParam::bool("b"), "Yes"), Ok(ParamValue::Bool(true))));
+        assert!(matches!(parse(&SubParam::bool("b"), "false"), Ok(ParamValue::Bool(false))));
+        assert!(matches!(parse(&SubParam::path("p"), "a/b"), Ok(ParamValue::Path(p)) if p == Path::new("a/b")));
+        assert!(matches!(parse(&SubParam::choice("c", &["fast", "slow"]), "slow"), Ok(ParamValue::Choice(c)) if c == "slow"));
+        assert!(matches!(parse(&SubParam::duration("d"), "2m"), Ok(ParamValue::Duration(d)) if d == Duration::from_secs(120)));
+        assert!(matches!(parse(&SubParam::uint("u"), "42"), Ok(ParamValue::UInt(42))));
+        assert!(matches!(parse(&SubParam::char("c"), "é"), Ok(ParamValue::Char('é'))));
+        assert!(matches!(parse(&SubParam::url("u"), "https://x.org"), Ok(ParamValue::Url(u)) if u == "https://x.org"));
+    }
+
+    #[test]
+    fn invalid_values_of_each_type() {
+        let cases = [
+            (SubParam::bool("b"), "maybe", "bool"),
+            (SubParam::path("p").must_exist(), "/does/not/exist", "existing path"),
+            (SubParam::choice("c", &["fast", "slow"]), "Fast", "one of fast, slow"),
+            (SubParam::duration("d"), "1m5", "duration"),
+            (SubParam::uint("u"), "-1", "uint"),
+            (SubParam::char("c"), "ab", "char"),
+            (SubParam::url("u"), "example.com", "url"),
+        ];
+        for (subparam, raw, type_info) in cases.iter() {
+            match parse(subparam, raw) {
+                Err(ClitcError::InvalidValue { option, subparam: name, index, raw: found, expected }) => {
+                    assert_eq!((option.as_str(), index, found.as_str()), ("--opt", 1, *raw));
+                    assert_eq!((name, expected), (subparam.get_name(), subparam.type_info()));
+                    assert!(subparam.type_info().starts_with(type_info));
+                },
+                Err(err) => panic!("{}", err),
+                Ok(val) => panic!("{} was parsed as {}", raw, val),
+            }
+        }
+    }
+
+    #[test]
+    fn choices_read_as_strings() {
+        let val = ParamValue::Choice(String::from("fast"));
+        assert_eq!(val.type_name(), "choice");
+        assert_eq!(String::try_from(&val).unwrap(), "fast");
+        assert!(i64::try_from(&val).is_err());
     }
 }
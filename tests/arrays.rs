@@ -0,0 +1,70 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::clitc_error::ClitcError;
+use common::{
+    modes,
+    parse,
+    parse_err,
+    strings,
+};
+
+const SPEC: &str = r#"{ "options": [
+    { "short": "-v", "name": "--verbose" },
+    { "short": "-f", "name": "--files", "params": [ { "ord": 0, "name": "files", "type": "array" } ] },
+    { "short": "-p", "name": "--pair", "params": [
+        { "ord": 0, "name": "items", "type": "array", "arity": 2 },
+        { "ord": 1, "name": "n", "type": "int" }
+    ] },
+    { "short": "-s", "name": "--some", "params": [ { "ord": 0, "name": "items", "type": "array", "arity": [1, 2] } ] },
+    { "short": "-t", "name": "--tags", "params": [ { "ord": 0, "name": "tags", "type": "array", "delimiter": "," } ] }
+], "positionals": [ { "ord": 0, "name": "input", "type": "string" } ] }"#;
+
+#[test]
+fn arrays_stop_at_the_next_option() {
+    for cli_params in modes(SPEC) {
+        let res = parse(&cli_params, "-f a b -v");
+        assert_eq!(res.get::<Vec<String>>("--files", "files").unwrap(), Some(strings(&["a", "b"])));
+        assert!(res.is_present("--verbose"));
+    }
+}
+
+#[test]
+fn arity_leaves_arguments_for_later_subparams() {
+    for cli_params in modes(SPEC) {
+        let res = parse(&cli_params, "-p a b 3 in");
+        assert_eq!(res.get::<Vec<String>>("--pair", "items").unwrap(), Some(strings(&["a", "b"])));
+        assert_eq!(res.get::<i64>("--pair", "n").unwrap(), Some(3));
+        assert_eq!(res.positional::<String>("input").unwrap().as_deref(), Some("in"));
+
+        let res = parse(&cli_params, "-s a b in");
+        assert_eq!(res.get::<Vec<String>>("--some", "items").unwrap(), Some(strings(&["a", "b"])));
+        assert_eq!(res.positional::<String>("input").unwrap().as_deref(), Some("in"));
+    }
+}
+
+#[test]
+fn arity_is_checked() {
+    for cli_params in modes(SPEC) {
+        match parse_err(&cli_params, "-s -v") {
+            ClitcError::WrongArity { found, .. } => assert_eq!(found, 0),
+            err => panic!("{}", err),
+        }
+        match parse_err(&cli_params, "-p a") {
+            ClitcError::WrongArity { expected, found, .. } => assert_eq!((expected.as_str(), found), ("exactly 2", 1)),
+            err => panic!("{}", err),
+        }
+    }
+}
+
+#[test]
+fn delimited_arrays_take_one_argument() {
+    for cli_params in modes(SPEC) {
+        let res = parse(&cli_params, "-t a,b,c in");
+        assert_eq!(res.get::<Vec<String>>("--tags", "tags").unwrap(), Some(strings(&["a", "b", "c"])));
+        assert_eq!(res.positional::<String>("input").unwrap().as_deref(), Some("in"));
+    }
+}
@@ -0,0 +1,99 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::params::{
+    CliParameters,
+    Command,
+    Param,
+    Positional,
+    SubParam,
+};
+use clitc::clitc_error::ClitcError;
+use common::{
+    parse,
+    strings,
+};
+
+fn copy(dest: SubParam, files: SubParam) -> Result<CliParameters, ClitcError> {
+    return CliParameters::builder()
+        .option(Param::new("--copy").short("-c").arg(dest).arg(files))
+        .build();
+}
+
+fn build_err(res: Result<CliParameters, ClitcError>) -> ClitcError {
+    match res {
+        Ok(_) => panic!("spec was accepted"),
+        Err(err) => err,
+    }
+}
+
+#[test]
+fn subparams_are_ordered_as_added() {
+    let cli_params = copy(SubParam::string("dest"), SubParam::array("files")).unwrap();
+    let res = parse(&cli_params, "-c out a b");
+    assert_eq!(res.get::<String>("--copy", "dest").unwrap(), Some(String::from("out")));
+    assert_eq!(res.get::<Vec<String>>("--copy", "files").unwrap(), Some(strings(&["a", "b"])));
+}
+
+#[test]
+fn explicit_ords_win_over_the_order_added() {
+    let cli_params = copy(SubParam::array("files").ord(1), SubParam::string("dest").ord(0)).unwrap();
+    let res = parse(&cli_params, "-c out a b");
+    assert_eq!(res.get::<String>("--copy", "dest").unwrap(), Some(String::from("out")));
+    assert_eq!(res.get::<Vec<String>>("--copy", "files").unwrap(), Some(strings(&["a", "b"])));
+}
+
+#[test]
+fn positionals_are_ordered_as_added() {
+    let cli_params = CliParameters::builder()
+        .positional(Positional::new(SubParam::string("src")))
+        .positional(Positional::new(SubParam::string("dest")))
+        .build()
+        .unwrap();
+    let res = parse(&cli_params, "a b");
+    assert_eq!(res.positional::<String>("src").unwrap(), Some(String::from("a")));
+    assert_eq!(res.positional::<String>("dest").unwrap(), Some(String::from("b")));
+}
+
+#[test]
+fn builds_the_same_spec_as_json() {
+    let built = CliParameters::builder()
+        .option(Param::new("--lifetime").short("-l").arg(SubParam::int("secs")))
+        .subcommand(Command::new("run").option(Param::new("--dry")))
+        .build()
+        .unwrap();
+    let spec = r#"{ "options": [ { "short": "-l", "name": "--lifetime", "params": [ { "ord": 0, "name": "secs", "type": "int" } ] } ],
+        "subcommands": [ { "name": "run", "options": [ { "name": "--dry" } ] } ] }"#;
+    let loaded = CliParameters::from_str(spec).unwrap();
+    assert_eq!(built.to_json().unwrap(), loaded.to_json().unwrap());
+}
+
+#[test]
+fn validates_like_the_json_loader() {
+    let built = build_err(copy(SubParam::string("dest"), SubParam::array("files").ord(0)));
+    let loaded = build_err(CliParameters::from_str(r#"{ "options": [ { "short": "-c", "name": "--copy", "params": [
+        { "ord": 0, "name": "dest", "type": "string" }, { "ord": 0, "name": "files", "type": "array" } ] } ] }"#));
+    assert!(matches!(built, ClitcError::DuplicateOrd { .. }), "{}", built);
+    assert_eq!(built.to_string(), loaded.to_string());
+
+    let built = build_err(CliParameters::builder().option(Param::new("--verbose").short("-v")).option(Param::new("--version").short("-v")).build());
+    let loaded = build_err(CliParameters::from_str(r#"{ "options": [ { "short": "-v", "name": "--verbose" }, { "short": "-v", "name": "--version" } ] }"#));
+    assert!(matches!(built, ClitcError::ConflictingIdentifier { .. }), "{}", built);
+    assert_eq!(built.to_string(), loaded.to_string());
+
+    let built = build_err(CliParameters::builder().option(Param::new("--jobs").arg(SubParam::int("n").min(4.0).max(2.0))).build());
+    assert!(matches!(built, ClitcError::WrongFormat { .. }), "{}", built);
+}
+
+#[test]
+fn specs_can_be_changed_at_runtime() {
+    let cli_params = copy(SubParam::string("dest"), SubParam::array("files")).unwrap();
+    let changed = cli_params.to_builder().option(Param::new("--force").short("-f")).build().unwrap();
+    let res = parse(&changed, "-f -c out a");
+    assert!(res.is_present("--force"));
+    assert!(res.is_present("--copy"));
+    assert!(build_err(cli_params.to_builder().option(Param::new("--clear").short("-c")).build()).to_string().contains("-c"));
+}
@@ -0,0 +1,43 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use clitc::clitc_error::ClitcError;
+use clitc::params::{
+    CliParameters,
+    ParseResult,
+};
+
+/// Arguments of a command line, split at whitespace
+pub fn args(line: &str) -> Vec<String> {
+    return line.split_whitespace().map(|x| x.to_string()).collect();
+}
+
+pub fn strings(list: &[&str]) -> Vec<String> {
+    return list.iter().map(|x| x.to_string()).collect();
+}
+
+/// Parsers of the spec processing options by location and sequentially
+pub fn modes(spec: &str) -> Vec<CliParameters> {
+    let location = CliParameters::from_str(spec).unwrap();
+    let mut sequential = CliParameters::from_str(spec).unwrap();
+    sequential.set_sequential_processing(true);
+    return vec![location, sequential];
+}
+
+/// Result of a command line which has to parse
+pub fn parse(cli_params: &CliParameters, line: &str) -> ParseResult {
+    return cli_params.parse_vec(args(line)).unwrap_or_else(|err| panic!("`{}`: {}", line, err));
+}
+
+/// Error of a command line which must not parse
+pub fn parse_err(cli_params: &CliParameters, line: &str) -> ClitcError {
+    return expect_err(cli_params.parse_vec(args(line)), line);
+}
+
+/// Error of a parse which must fail, `what` names it in the panic otherwise
+pub fn expect_err(res: Result<ParseResult, ClitcError>, what: &str) -> ClitcError {
+    match res {
+        Ok(res) => panic!("`{}` was accepted, unmatched: {:?}", what, res.unmatched),
+        Err(err) => err,
+    }
+}
@@ -0,0 +1,109 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+extern crate serde_json;
+
+mod common;
+
+use clitc::clitc_error::ClitcError;
+use clitc::params::{CliParameters, ConfigFile, Layer, Source};
+use common::{
+    expect_err,
+    strings,
+};
+use serde_json::json;
+
+const SPEC: &str = r#"{ "options": [
+    { "short": "-l", "name": "--lifetime", "params": [
+        { "ord": 0, "name": "secs", "type": "int" },
+        { "ord": 1, "name": "expected_val", "type": "num", "default": 1.5 }
+    ] },
+    { "name": "--tags", "params": [ { "ord": 0, "name": "tags", "type": "array" } ] }
+] }"#;
+
+#[test]
+fn empty_list_is_rejected() {
+    let cli_params = CliParameters::from_str(SPEC).unwrap();
+    let config = ConfigFile::from_value("test.json", json!({ "lifetime": { "secs": [] } })).unwrap();
+    match expect_err(cli_params.parse_vec_layered(Vec::new(), Some(&config), &[Layer::Config]), "lifetime.secs") {
+        ClitcError::InvalidConfig { key, reason, .. } => assert_eq!((key.as_str(), reason.as_str()), ("lifetime.secs", "has no value")),
+        err => panic!("{}", err),
+    }
+
+    // Arrays may be empty
+    let config = ConfigFile::from_value("test.json", json!({ "tags": [] })).unwrap();
+    let res = cli_params.parse_vec_layered(Vec::new(), Some(&config), &[Layer::Config]).unwrap();
+    assert_eq!(res.get::<Vec<String>>("--tags", "tags").unwrap(), Some(Vec::new()));
+}
+
+#[test]
+fn subparam_without_tokens_is_an_error() {
+    let subparam = clitc::params::SubParam::int("secs");
+    match subparam.match_with("--lifetime", &mut Vec::new()) {
+        Err(ClitcError::WrongArity { name, found, .. }) => assert_eq!((name.as_str(), found), ("--lifetime secs", 0)),
+        Err(err) => panic!("{}", err),
+        Ok(_) => panic!("a subparam without tokens was accepted"),
+    }
+}
+
+#[test]
+fn layers_follow_precedence() {
+    let spec = r#"{ "options": [ { "short": "-n", "name": "--level", "params": [
+        { "ord": 0, "name": "n", "type": "int", "env": "CLITC_TEST_CONFIG_LEVEL", "default": 1 }
+    ] } ] }"#;
+    std::env::set_var("CLITC_TEST_CONFIG_LEVEL", "2");
+    let cli_params = CliParameters::from_str(spec).unwrap();
+    let config = ConfigFile::from_value("test.json", json!({ "level": 4 })).unwrap();
+    let cli = strings(&["-n", "3"]);
+    let all = [Layer::Cli, Layer::Env, Layer::Config];
+
+    let res = cli_params.parse_vec_layered(cli.clone(), Some(&config), &all).unwrap();
+    assert_eq!(res.get::<i64>("--level", "n").unwrap(), Some(3));
+    assert_eq!(res.source("--level", "n"), Some(&Source::Cli(1)));
+
+    let res = cli_params.parse_vec_layered(Vec::new(), Some(&config), &all).unwrap();
+    assert_eq!(res.get::<i64>("--level", "n").unwrap(), Some(2));
+    assert_eq!(res.source("-n", "n"), Some(&Source::Env(String::from("CLITC_TEST_CONFIG_LEVEL"))));
+
+    let res = cli_params.parse_vec_layered(cli, Some(&config), &[Layer::Config, Layer::Env, Layer::Cli]).unwrap();
+    assert_eq!(res.get::<i64>("--level", "n").unwrap(), Some(4));
+    assert_eq!(res.source("--level", "n"), Some(&Source::Config { file: String::from("test.json"), key: String::from("level") }));
+
+    // Layers which are left out are not read at all
+    let res = cli_params.parse_vec_layered(Vec::new(), Some(&config), &[Layer::Cli]).unwrap();
+    assert!(!res.is_present("--level"));
+}
+
+#[test]
+fn lists_are_occurrences_of_repeated_options() {
+    let spec = r#"{ "options": [
+        { "short": "-i", "name": "--inc", "multiple": true, "params": [ { "ord": 0, "name": "path", "type": "string" } ] },
+        { "name": "--range", "multiple": true, "params": [
+            { "ord": 0, "name": "from", "type": "int" },
+            { "ord": 1, "name": "to", "type": "int", "default": 9 }
+        ] }
+    ] }"#;
+    let cli_params = CliParameters::from_str(spec).unwrap();
+    let config = ConfigFile::from_value("test.json", json!({ "inc": ["a", "b"], "range": [[1, 2], { "from": 3 }] })).unwrap();
+    let res = cli_params.parse_vec_layered(Vec::new(), Some(&config), &[Layer::Cli, Layer::Config]).unwrap();
+    assert_eq!(res.values_of::<String>("--inc", "path").unwrap(), strings(&["a", "b"]));
+    assert_eq!(res.count("--inc"), 2);
+    assert_eq!(res.values_of::<i64>("--range", "from").unwrap(), vec![1, 3]);
+    assert_eq!(res.values_of::<i64>("--range", "to").unwrap(), vec![2, 9]);
+
+    // A single value is one occurrence
+    let config = ConfigFile::from_value("test.json", json!({ "inc": "a" })).unwrap();
+    let res = cli_params.parse_vec_layered(Vec::new(), Some(&config), &[Layer::Cli, Layer::Config]).unwrap();
+    assert_eq!(res.values_of::<String>("--inc", "path").unwrap(), strings(&["a"]));
+    assert_eq!(res.occurrences("--inc").len(), 1);
+
+    // Occurrences on the command line win over the config file
+    let res = cli_params.parse_vec_layered(strings(&["-i", "c"]), Some(&config), &[Layer::Cli, Layer::Config]).unwrap();
+    assert_eq!(res.values_of::<String>("--inc", "path").unwrap(), strings(&["c"]));
+
+    let config = ConfigFile::from_value("test.json", json!({ "range": [[1, 2, 3]] })).unwrap();
+    match expect_err(cli_params.parse_vec_layered(Vec::new(), Some(&config), &[Layer::Config]), "range[0]") {
+        ClitcError::InvalidConfig { key, reason, .. } => assert_eq!((key.as_str(), reason.as_str()), ("range[0]", "has too many values")),
+        err => panic!("{}", err),
+    }
+}
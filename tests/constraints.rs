@@ -0,0 +1,112 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::params::CliParameters;
+use clitc::clitc_error::ClitcError;
+use common::{
+    modes,
+    parse,
+    parse_err,
+    strings,
+};
+
+const SPEC: &str = r#"{ "options": [
+    { "name": "--jobs", "params": [ { "ord": 0, "name": "n", "type": "int", "min": 1, "max": 16 } ] },
+    { "name": "--ratio", "params": [ { "ord": 0, "name": "r", "type": "num", "min": 0, "max": 1 } ] },
+    { "name": "--tag", "params": [ { "ord": 0, "name": "t", "type": "string", "min_len": 2, "max_len": 5, "pattern": "[a-z]+" } ] },
+    { "name": "--hosts", "params": [ { "ord": 0, "name": "list", "type": "array", "delimiter": ",", "min_items": 2, "max_items": 3 } ] }
+] }"#;
+
+fn violation(line: &str) -> (String, String, usize, String, String) {
+    let mut ret = vec![];
+    for cli_params in modes(SPEC) {
+        match parse_err(&cli_params, line) {
+            ClitcError::ConstraintViolation { option, subparam, index, raw, reason } => ret.push((option, subparam, index, raw, reason)),
+            err => panic!("{}", err),
+        }
+    }
+    // Both modes report the same violation
+    assert_eq!(ret[0], ret[1]);
+    return ret.remove(0);
+}
+
+fn load_err(spec: &str) -> (String, String) {
+    match CliParameters::from_str(spec) {
+        Err(ClitcError::WrongFormat { path, reason }) => (path, reason),
+        Err(err) => panic!("{}", err),
+        Ok(_) => panic!("spec was accepted: {}", spec),
+    }
+}
+
+#[test]
+fn values_within_constraints() {
+    for cli_params in modes(SPEC) {
+        let res = parse(&cli_params, "--jobs 16 --ratio 0.5 --tag ab --hosts a,b,c");
+        assert_eq!(res.get::<i64>("--jobs", "n").unwrap(), Some(16));
+        assert_eq!(res.get::<f64>("--ratio", "r").unwrap(), Some(0.5));
+        assert_eq!(res.get::<String>("--tag", "t").unwrap(), Some(String::from("ab")));
+        assert_eq!(res.get::<Vec<String>>("--hosts", "list").unwrap(), Some(strings(&["a", "b", "c"])));
+    }
+}
+
+#[test]
+fn min_and_max() {
+    let (option, subparam, index, raw, reason) = violation("--tag abc --jobs 0");
+    assert_eq!((option.as_str(), subparam.as_str(), index), ("--jobs", "n", 3));
+    assert_eq!((raw.as_str(), reason.as_str()), ("0", "must be at least 1"));
+    assert_eq!(violation("--jobs 17").4, "must be at most 16");
+    assert_eq!(violation("--ratio 1.5").4, "must be at most 1");
+    assert_eq!(violation("--ratio -0.1").4, "must be at least 0");
+}
+
+#[test]
+fn min_len_and_max_len() {
+    assert_eq!(violation("--tag a").4, "must be at least 2 characters long");
+    assert_eq!(violation("--tag abcdef").4, "must be at most 5 characters long");
+}
+
+#[test]
+fn patterns_are_anchored() {
+    // `[a-z]+` would find a match inside every one of these
+    for raw in ["ab1", "1ab", "a-b"].iter() {
+        let (_, _, _, found, reason) = violation(&format!("--tag {}", raw));
+        assert_eq!((found.as_str(), reason.as_str()), (*raw, "must match `[a-z]+`"));
+    }
+}
+
+#[test]
+fn min_items_and_max_items() {
+    let (option, _, index, raw, reason) = violation("--hosts a");
+    assert_eq!((option.as_str(), index, raw.as_str(), reason.as_str()), ("--hosts", 1, "a", "must have at least 2 items"));
+    assert_eq!(violation("--hosts a,b,c,d").4, "must have at most 3 items");
+}
+
+#[test]
+fn constraints_of_other_types_are_rejected() {
+    let (path, reason) = load_err(r#"{ "options": [ { "name": "--n", "params": [ { "ord": 0, "name": "n", "type": "string", "min": 1 } ] } ] }"#);
+    assert!(path.ends_with("min"), "{}", path);
+    assert_eq!(reason, "min is not supported by type string");
+    let (path, _) = load_err(r#"{ "options": [ { "name": "--n", "params": [ { "ord": 0, "name": "n", "type": "int", "pattern": "[0-9]" } ] } ] }"#);
+    assert!(path.ends_with("pattern"), "{}", path);
+    let (path, _) = load_err(r#"{ "options": [ { "name": "--n", "params": [ { "ord": 0, "name": "n", "type": "string", "min_items": 1 } ] } ] }"#);
+    assert!(path.ends_with("min_items"), "{}", path);
+}
+
+#[test]
+fn inverted_bounds_are_rejected() {
+    let (path, reason) = load_err(r#"{ "options": [ { "name": "--n", "params": [ { "ord": 0, "name": "n", "type": "int", "min": 5, "max": 2 } ] } ] }"#);
+    assert!(path.ends_with("max"), "{}", path);
+    assert_eq!(reason, "2 is lower than the minimum 5");
+    let (path, _) = load_err(r#"{ "options": [ { "name": "--n", "params": [ { "ord": 0, "name": "n", "type": "string", "min_len": 3, "max_len": 1 } ] } ] }"#);
+    assert!(path.ends_with("max_len"), "{}", path);
+}
+
+#[test]
+fn defaults_are_checked_at_load() {
+    let (path, reason) = load_err(r#"{ "options": [ { "name": "--n", "params": [ { "ord": 0, "name": "n", "type": "int", "max": 3, "default": 4 } ] } ] }"#);
+    assert!(path.ends_with("default"), "{}", path);
+    assert_eq!(reason, "default value `4` must be at most 3");
+}
@@ -0,0 +1,47 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+use std::any::Any;
+use clitc::params::CliParameters;
+
+const SPEC: &str = r#"{ "options": [ { "short": "-l", "name": "--lifetime", "params": [
+    { "ord": 0, "name": "secs", "type": "int", "default": 5 },
+    { "ord": 1, "name": "version", "type": "semver" }
+] } ] }"#;
+
+fn parse_major(raw: &str) -> Result<Box<dyn Any + Send + Sync>, String> {
+    let major: u64 = raw.split('.').next().and_then(|x| x.parse().ok()).ok_or_else(|| String::from("version"))?;
+    return Ok(Box::new(major));
+}
+
+#[test]
+fn builtin_type_names_are_rejected() {
+    let mut cli_params = CliParameters::from_str(SPEC).unwrap();
+    assert!(cli_params.register_parser("int", parse_major).is_err());
+    cli_params.register_parser("semver", parse_major).unwrap();
+
+    let res = cli_params.parse_str_whitespace("-l").unwrap();
+    assert_eq!(res.get::<i64>("--lifetime", "secs").unwrap(), Some(5));
+}
+
+#[test]
+fn custom_values_are_parsed() {
+    let mut cli_params = CliParameters::from_str(SPEC).unwrap();
+    cli_params.register_parser("semver", parse_major).unwrap();
+
+    let res = cli_params.parse_str_whitespace("-l 7 2.1.0").unwrap();
+    assert_eq!(res.custom::<u64>("--lifetime", "version").unwrap(), Some(&2));
+}
+
+#[test]
+fn unknown_types_fail_before_parsing() {
+    let data = r#"{ "options": [ { "name": "--count", "params": [ { "ord": 0, "name": "n", "type": "itn" } ] } ] }"#;
+    let cli_params = CliParameters::from_str(data).unwrap();
+    assert!(cli_params.check_types().is_err());
+    // Even if no token reaches the subparam
+    assert!(cli_params.parse_vec(Vec::new()).is_err());
+
+    let builder = CliParameters::from_str(data).unwrap().to_builder();
+    assert!(builder.build().is_err());
+}
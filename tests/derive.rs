@@ -0,0 +1,59 @@
+#![cfg(feature = "derive")]
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+use clitc::Clitc;
+use clitc::clitc_error::ClitcError;
+
+#[derive(Clitc)]
+struct Args {
+    #[clitc(short = "-v", descr = "Print more")]
+    verbose: bool,
+    #[clitc(name = "--lifetime", short = "-l", param = "secs", ord = 0)]
+    secs: Option<i64>,
+    #[clitc(name = "--lifetime", param = "expected_val", ord = 1)]
+    expected_val: Option<f64>,
+    #[clitc(short = "-n")]
+    name: String,
+    #[clitc(positional)]
+    input: String,
+}
+
+fn parse(line: &str) -> Result<Args, ClitcError> {
+    let res = Args::cli_parameters()?.parse_str_whitespace(line)?;
+    return Args::from_parse_result(&res);
+}
+
+#[test]
+fn fields_are_filled() {
+    let args = parse("-v -l 30 1.5 -n test in.txt").unwrap();
+    assert!(args.verbose);
+    assert_eq!(args.secs, Some(30));
+    assert_eq!(args.expected_val, Some(1.5));
+    assert_eq!(args.name, "test");
+    assert_eq!(args.input, "in.txt");
+}
+
+#[test]
+fn optional_fields_may_be_missing() {
+    let args = parse("--name test in.txt").unwrap();
+    assert!(!args.verbose);
+    assert_eq!(args.secs, None);
+    assert_eq!(args.expected_val, None);
+}
+
+#[test]
+fn required_fields_are_reported() {
+    match parse("-v in.txt") {
+        Err(ClitcError::MissingRequired { missing }) => assert_eq!(missing, vec![(String::from("--name"), None)]),
+        Err(err) => panic!("{}", err),
+        Ok(_) => panic!("missing --name was accepted"),
+    }
+}
+
+#[test]
+fn macro_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/derive/*.rs");
+}
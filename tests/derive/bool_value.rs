@@ -0,0 +1,9 @@
+use clitc::Clitc;
+
+#[derive(Clitc)]
+struct Args {
+    #[clitc(positional)]
+    verbose: bool,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use clitc::Clitc;
+
+#[derive(Clitc)]
+struct Args {
+    #[clitc(name = "--lifetime", short = "-l", param = "secs")]
+    secs: Option<i64>,
+    #[clitc(name = "--lifetime", short = "-t", param = "expected_val")]
+    expected_val: Option<f64>,
+}
+
+fn main() {}
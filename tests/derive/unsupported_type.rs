@@ -0,0 +1,8 @@
+use clitc::Clitc;
+
+#[derive(Clitc)]
+struct Args {
+    count: Option<i32>,
+}
+
+fn main() {}
@@ -0,0 +1,40 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::params::{
+    CliParameters,
+    Layer,
+};
+use clitc::clitc_error::ClitcError;
+use common::expect_err;
+
+/// Every test uses variables of its own, as tests run in parallel
+fn spec(var: &str) -> String {
+    return format!(r#"{{ "options": [ {{ "short": "-l", "name": "--lifetime", "env": "{}", "params": [
+        {{ "ord": 0, "name": "secs", "type": "int" }},
+        {{ "ord": 1, "name": "expected_val", "type": "num" }}
+    ] }} ] }}"#, var);
+}
+
+#[test]
+fn option_values_from_env() {
+    std::env::set_var("CLITC_TEST_ENV_VALUES", "30 1.5");
+    let cli_params = CliParameters::from_str(&spec("CLITC_TEST_ENV_VALUES")).unwrap();
+    let res = cli_params.parse_vec_layered(Vec::new(), None, &[Layer::Cli, Layer::Env]).unwrap();
+    assert_eq!(res.get::<i64>("--lifetime", "secs").unwrap(), Some(30));
+    assert_eq!(res.get::<f64>("--lifetime", "expected_val").unwrap(), Some(1.5));
+}
+
+#[test]
+fn leftover_env_values_are_rejected() {
+    std::env::set_var("CLITC_TEST_ENV_LEFTOVER", "30 1.5 junk");
+    let cli_params = CliParameters::from_str(&spec("CLITC_TEST_ENV_LEFTOVER")).unwrap();
+    let res = cli_params.parse_vec_layered(Vec::new(), None, &[Layer::Cli, Layer::Env]);
+    match expect_err(res, "CLITC_TEST_ENV_LEFTOVER") {
+        ClitcError::InvalidEnv { var, reason } => assert_eq!((var.as_str(), reason.as_str()), ("CLITC_TEST_ENV_LEFTOVER", "has too many values")),
+        err => panic!("{}", err),
+    }
+}
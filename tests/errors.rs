@@ -0,0 +1,51 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::params::CliParameters;
+use clitc::clitc_error::ClitcError;
+use common::{
+    modes,
+    parse_err,
+};
+
+const SPEC: &str = r#"{ "options": [
+    { "short": "-v", "name": "--verbose" },
+    { "short": "-l", "name": "--lifetime", "params": [
+        { "ord": 0, "name": "secs", "type": "int" },
+        { "ord": 1, "name": "expected_val", "type": "num" }
+    ] }
+], "positionals": [ { "ord": 0, "name": "count", "type": "uint" } ] }"#;
+
+fn invalid_value(cli_params: &CliParameters, line: &str) -> (String, String, usize, String, String) {
+    match parse_err(cli_params, line) {
+        ClitcError::InvalidValue { option, subparam, index, raw, expected } => (option, subparam, index, raw, expected),
+        err => panic!("`{}`: {}", line, err),
+    }
+}
+
+#[test]
+fn invalid_values_name_their_token() {
+    for cli_params in modes(SPEC) {
+        let err = invalid_value(&cli_params, "-v -l abc 1.5");
+        assert_eq!(err, (String::from("--lifetime"), String::from("secs"), 2, String::from("abc"), String::from("int")));
+        let err = invalid_value(&cli_params, "-l 5 x.5 -v");
+        assert_eq!((err.1.as_str(), err.2, err.3.as_str()), ("expected_val", 2, "x.5"));
+    }
+}
+
+#[test]
+fn invalid_positionals_name_their_token() {
+    let cli_params = CliParameters::from_str(SPEC).unwrap();
+    let (_, subparam, index, raw, _) = invalid_value(&cli_params, "-v -l 5 1.5 many");
+    assert_eq!((subparam.as_str(), index, raw.as_str()), ("count", 4, "many"));
+}
+
+#[test]
+fn invalid_values_are_displayed() {
+    let cli_params = CliParameters::from_str(SPEC).unwrap();
+    let err = parse_err(&cli_params, "-l abc 1.5");
+    assert_eq!(err.to_string(), "--lifetime: expected int for `secs`, got `abc` (arg 1)");
+}
@@ -0,0 +1,68 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::params::{
+    CliParameters,
+    TokenStyle,
+};
+use common::{
+    modes,
+    parse,
+};
+
+const SPEC: &str = r#"{ "options": [
+    { "short": "-v", "name": "--verbose" },
+    { "short": "-1", "name": "--one" },
+    { "short": "-r", "name": "--range", "params": [
+        { "ord": 0, "name": "from", "type": "int" },
+        { "ord": 1, "name": "to", "type": "num" }
+    ] }
+], "positionals": [
+    { "ord": 0, "name": "first", "type": "string" },
+    { "ord": 1, "name": "second", "type": "string" }
+] }"#;
+
+#[test]
+fn options_after_terminator_are_positionals() {
+    for cli_params in modes(SPEC) {
+        let res = parse(&cli_params, "-v -- --verbose -r");
+        assert!(res.is_present("--verbose"));
+        assert!(!res.is_present("--range"));
+        assert_eq!(res.positional::<String>("first").unwrap().as_deref(), Some("--verbose"));
+        assert_eq!(res.positional::<String>("second").unwrap().as_deref(), Some("-r"));
+        assert!(res.unmatched.is_empty());
+    }
+}
+
+#[test]
+fn only_the_first_terminator_is_removed() {
+    let res = parse(&CliParameters::from_str(SPEC).unwrap(), "a -- --");
+    assert_eq!(res.positional::<String>("first").unwrap().as_deref(), Some("a"));
+    assert_eq!(res.positional::<String>("second").unwrap().as_deref(), Some("--"));
+}
+
+#[test]
+fn negative_numbers_are_values() {
+    for cli_params in modes(SPEC) {
+        // `-1` is a short, but taken as number by `from`
+        let res = parse(&cli_params, "-r -1 -2.5 -1");
+        assert_eq!(res.get::<i64>("--range", "from").unwrap(), Some(-1));
+        assert_eq!(res.get::<f64>("--range", "to").unwrap(), Some(-2.5));
+        assert!(res.is_present("--one"));
+    }
+}
+
+#[test]
+fn negative_numbers_are_not_clusters() {
+    for mut cli_params in modes(SPEC) {
+        cli_params.set_token_style(TokenStyle::Gnu);
+        let res = parse(&cli_params, "-r -11 -2.5 -1v");
+        assert_eq!(res.get::<i64>("--range", "from").unwrap(), Some(-11));
+        assert_eq!(res.get::<f64>("--range", "to").unwrap(), Some(-2.5));
+        // Without a subparam waiting for a number, `-1v` is a cluster
+        assert!(res.is_present("--one") && res.is_present("--verbose"));
+    }
+}
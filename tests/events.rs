@@ -0,0 +1,49 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+};
+use clitc::events::{
+    Event,
+    EventHandler,
+    WhitespaceSplitter,
+};
+use clitc::params::{
+    CliParameters,
+    ParamValue,
+};
+
+const SPEC: &str = r#"{ "options": [ { "short": "-v", "name": "--verbose" } ], "subcommands": [
+    { "name": "db", "subcommands": [
+        { "name": "migrate", "options": [
+            { "name": "--dry-run" },
+            { "short": "-c", "name": "--count", "params": [ { "ord": 0, "name": "n", "type": "int" } ] }
+        ], "positionals": [ { "ord": 0, "name": "target", "type": "string", "required": false } ] }
+    ] }
+] }"#;
+
+/// Arguments the callback was invoked with
+type Calls = Rc<RefCell<Vec<HashMap<String, ParamValue>>>>;
+
+#[test]
+fn subcommand_callbacks_get_options() {
+    let cli_params = CliParameters::from_str(SPEC).unwrap();
+    let calls: Calls = Rc::new(RefCell::new(Vec::new()));
+    let mut handler = EventHandler::<_, Calls, ()>::new(cli_params, WhitespaceSplitter, true, Rc::clone(&calls));
+    let mut events = HashMap::new();
+    events.insert(String::from("db migrate"), Event::Callback(Rc::new(|calls: Calls, args| calls.borrow_mut().push(args))));
+    handler.attach(events);
+
+    handler.pass_command(String::from("-v db migrate --dry-run -c 3 v2")).unwrap();
+    let calls = calls.borrow();
+    let args = &calls[0];
+    assert_eq!(args.len(), 4);
+    assert!(matches!(args.get("--dry-run"), Some(ParamValue::Bool(true))));
+    assert!(matches!(args.get("--verbose"), Some(ParamValue::Bool(true))));
+    assert!(matches!(args.get("--count n"), Some(ParamValue::Int(3))));
+    assert!(matches!(args.get("target"), Some(ParamValue::String(x)) if x == "v2"));
+}
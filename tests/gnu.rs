@@ -0,0 +1,66 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::params::{
+    CliParameters,
+    TokenStyle,
+};
+use clitc::clitc_error::ClitcError;
+use common::expect_err;
+
+const SPEC: &str = r#"{ "options": [
+    { "short": "-v", "name": "--verbose" },
+    { "short": "-x", "name": "--extra" },
+    { "short": "-n", "name": "--number", "params": [ { "ord": 0, "name": "n", "type": "int" } ] }
+], "positionals": [ { "ord": 0, "name": "input", "type": "string" } ] }"#;
+
+fn gnu_params() -> CliParameters {
+    let mut cli_params = CliParameters::from_str(SPEC).unwrap();
+    cli_params.set_token_style(TokenStyle::Gnu);
+    return cli_params;
+}
+
+#[test]
+fn clustered_flags() {
+    let res = gnu_params().parse_str_whitespace("-vx in").unwrap();
+    assert!(res.is_present("--verbose") && res.is_present("--extra"));
+    assert_eq!(res.positional::<String>("input").unwrap().as_deref(), Some("in"));
+}
+
+#[test]
+fn attached_values() {
+    let cli_params = gnu_params();
+    for line in ["-n5 in", "-vn5 in", "--number=5 in", "-n 5 in"] {
+        let res = cli_params.parse_str_whitespace(line).unwrap();
+        assert_eq!(res.get::<i64>("--number", "n").unwrap(), Some(5), "{}", line);
+        assert_eq!(res.positional::<String>("input").unwrap().as_deref(), Some("in"), "{}", line);
+    }
+    let res = cli_params.parse_str_whitespace("-vn5").unwrap();
+    assert!(res.is_present("--verbose"));
+}
+
+#[test]
+fn unknown_clusters_stay_as_they_are() {
+    let res = gnu_params().parse_str_whitespace("-vq").unwrap();
+    assert!(!res.is_present("--verbose"));
+    assert_eq!(res.positional::<String>("input").unwrap().as_deref(), Some("-vq"));
+}
+
+#[test]
+fn strict_style_matches_whole_tokens() {
+    let cli_params = CliParameters::from_str(SPEC).unwrap();
+    let res = cli_params.parse_str_whitespace("-vx").unwrap();
+    assert!(!res.is_present("--verbose"));
+    assert_eq!(res.positional::<String>("input").unwrap().as_deref(), Some("-vx"));
+}
+
+#[test]
+fn flags_do_not_take_attached_values() {
+    match expect_err(gnu_params().parse_str_whitespace("--verbose=3 in"), "--verbose=3 in") {
+        ClitcError::WrongArity { name, expected, found } => assert_eq!((name.as_str(), expected.as_str(), found), ("--verbose", "exactly 0", 1)),
+        err => panic!("{}", err),
+    }
+}
@@ -0,0 +1,126 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::help::HelpFormatter;
+use clitc::params::CliParameters;
+use clitc::clitc_error::ClitcError;
+use common::{
+    modes,
+    parse,
+    parse_err,
+    strings,
+};
+
+const SPEC: &str = r#"{ "name": "app", "options": [
+    { "short": "-i", "name": "--input", "params": [ { "ord": 0, "name": "p", "type": "string" } ] },
+    { "short": "-v", "name": "--verbose" }
+] }"#;
+
+const PAGE_SPEC: &str = r#"{ "name": "app", "descr": "Serves files", "options": [
+    { "short": "-v", "name": "--verbose", "descr": "Print more", "multiple": "count" },
+    { "short": "-l", "name": "--lifetime", "descr": "How long the service runs before it shuts itself down again", "env": "APP_LIFETIME", "params": [
+        { "ord": 0, "name": "secs", "type": "int", "default": 60, "env": "APP_SECS" },
+        { "ord": 1, "name": "expected_val", "type": "num" }
+    ] },
+    { "name": "--mode", "group": "Network", "required": true, "params": [ { "ord": 0, "name": "mode", "type": "choice", "choices": ["tcp", "udp"] } ] },
+    { "name": "--port", "group": "Network", "params": [ { "ord": 0, "type": "uint" } ] }
+], "positionals": [ { "ord": 0, "name": "input", "type": "path" } ],
+"subcommands": [ { "name": "serve", "descr": "Start the server" } ] }"#;
+
+const USAGE: &str = "\
+Usage: app [-v]... [-l <secs> <expected_val>] --mode <mode>
+           [--port <uint>] [<input>]
+       app <command> ...";
+
+/// Parsers of the spec with the built-in help, by location and sequentially
+fn help_modes() -> Vec<CliParameters> {
+    let mut modes = modes(SPEC);
+    for cli_params in modes.iter_mut() {
+        cli_params.enable_help();
+    }
+    return modes;
+}
+
+#[test]
+fn help_is_off_by_default() {
+    let res = parse(&CliParameters::from_str(SPEC).unwrap(), "-v --help");
+    assert_eq!(res.unmatched, vec![String::from("--help")]);
+}
+
+#[test]
+fn help_flags_at_option_positions() {
+    for cli_params in help_modes() {
+        for line in ["--help", "-v -h", "-i x --help"] {
+            match parse_err(&cli_params, line) {
+                ClitcError::HelpRequested { help } => assert!(help.starts_with("Usage: app"), "{}", help),
+                err => panic!("`{}`: {}", line, err),
+            }
+        }
+    }
+}
+
+#[test]
+fn help_flags_as_values() {
+    for cli_params in help_modes() {
+        let res = parse(&cli_params, "-i -h");
+        assert_eq!(res.get::<String>("--input", "p").unwrap().as_deref(), Some("-h"));
+        let res = parse(&cli_params, "-i --help -v");
+        assert_eq!(res.get::<String>("--input", "p").unwrap().as_deref(), Some("--help"));
+    }
+}
+
+#[test]
+fn usage_lists_subcommands_on_a_line_of_their_own() {
+    let cli_params = CliParameters::from_str(PAGE_SPEC).unwrap();
+    let usage = HelpFormatter::new("app").width(60).usage(&cli_params, &[]).unwrap();
+    assert_eq!(usage, USAGE);
+}
+
+#[test]
+fn command_page() {
+    let cli_params = CliParameters::from_str(PAGE_SPEC).unwrap();
+    let page = HelpFormatter::new("app").width(60).render(&cli_params, &[]).unwrap();
+    let expected = [USAGE, "\n\n\
+Serves files
+
+Options:
+  -v, --verbose      Print more
+                     [repeatable]
+  -l, --lifetime <secs> <expected_val>
+                     How long the service runs before it
+                     shuts itself down again
+                     [env: APP_LIFETIME]
+                     <secs>: int [default: 60] [env:
+                     APP_SECS]
+                     <expected_val>: num
+
+Network:
+      --mode <mode>  [required]
+                     <mode>: one of tcp, udp
+      --port <uint>
+
+Arguments:
+  [<input>]          path
+
+Commands:
+  serve              Start the server"].concat();
+    assert_eq!(page, expected);
+}
+
+#[test]
+fn option_page() {
+    let cli_params = CliParameters::from_str(PAGE_SPEC).unwrap();
+    let page = HelpFormatter::new("app").width(60).render(&cli_params, &strings(&["--lifetime"])).unwrap();
+    assert_eq!(page, "\
+Usage: app -l <secs> <expected_val>
+
+  -l, --lifetime <secs> <expected_val>
+    How long the service runs before it shuts itself down
+    again
+    [env: APP_LIFETIME]
+    <secs>: int [default: 60] [env: APP_SECS]
+    <expected_val>: num");
+}
@@ -0,0 +1,102 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::params::CliParameters;
+use clitc::clitc_error::ClitcError;
+use common::{
+    modes,
+    parse,
+    parse_err,
+};
+
+fn load_err(spec: &str) -> ClitcError {
+    match CliParameters::from_str(spec) {
+        Ok(_) => panic!("spec was accepted: {}", spec),
+        Err(err) => err,
+    }
+}
+
+#[test]
+fn shorts_collide_with_shorts() {
+    let spec = r#"{ "options": [ { "short": "-v", "name": "--verbose" }, { "short": "-v", "name": "--version" } ] }"#;
+    match load_err(spec) {
+        ClitcError::ConflictingIdentifier { path, identifier, other } => {
+            assert_eq!((path.as_str(), identifier.as_str(), other.as_str()), ("options[1].short", "-v", "options[0].short"));
+        },
+        err => panic!("{}", err),
+    }
+}
+
+#[test]
+fn shorts_collide_with_names() {
+    let spec = r#"{ "options": [ { "name": "-x" }, { "short": "-x", "name": "--extra" } ] }"#;
+    match load_err(spec) {
+        ClitcError::ConflictingIdentifier { path, identifier, other } => {
+            assert_eq!((path.as_str(), identifier.as_str(), other.as_str()), ("options[1].short", "-x", "options[0].name"));
+        },
+        err => panic!("{}", err),
+    }
+}
+
+#[test]
+fn collisions_in_subcommands() {
+    let spec = r#"{ "subcommands": [ { "name": "db", "options": [ { "name": "--url" }, { "name": "--url" } ] } ] }"#;
+    match load_err(spec) {
+        ClitcError::ConflictingIdentifier { path, .. } => assert!(path.ends_with("options[1].name"), "{}", path),
+        err => panic!("{}", err),
+    }
+}
+
+#[test]
+fn prefixes_conflict_with_abbreviations() {
+    let options = r#""options": [ { "name": "--port" }, { "name": "--port-range" } ]"#;
+    assert!(CliParameters::from_str(&format!("{{ {} }}", options)).is_ok());
+    match load_err(&format!(r#"{{ "abbreviations": true, {} }}"#, options)) {
+        ClitcError::AmbiguousPrefix { path, prefix, identifier, other } => {
+            assert_eq!((path.as_str(), prefix.as_str()), ("options[0].name", "--port"));
+            assert_eq!((identifier.as_str(), other.as_str()), ("--port-range", "options[1].name"));
+        },
+        err => panic!("{}", err),
+    }
+}
+
+const ABBREVIATED: &str = r#"{ "abbreviations": true, "options": [
+    { "short": "-l", "name": "--lifetime", "params": [ { "ord": 0, "name": "secs", "type": "int" } ] },
+    { "name": "--limit", "params": [ { "ord": 0, "name": "n", "type": "int" } ] },
+    { "name": "--verbose" }
+] }"#;
+
+#[test]
+fn unambiguous_prefixes() {
+    for cli_params in modes(ABBREVIATED) {
+        let res = parse(&cli_params, "--life 5 --v --lim 2");
+        assert_eq!(res.get::<i64>("--lifetime", "secs").unwrap(), Some(5));
+        assert_eq!(res.get::<i64>("--limit", "n").unwrap(), Some(2));
+        assert!(res.is_present("--verbose"));
+    }
+}
+
+#[test]
+fn ambiguous_prefixes_are_errors() {
+    for cli_params in modes(ABBREVIATED) {
+        match parse_err(&cli_params, "--verbose --li 5") {
+            ClitcError::AmbiguousOption { token, index, mut candidates } => {
+                candidates.sort();
+                assert_eq!((token.as_str(), index), ("--li", 1));
+                assert_eq!(candidates, vec![String::from("--lifetime"), String::from("--limit")]);
+            },
+            err => panic!("{}", err),
+        }
+    }
+}
+
+#[test]
+fn prefixes_need_abbreviations() {
+    let cli_params = CliParameters::from_str(&ABBREVIATED.replace(r#""abbreviations": true, "#, "")).unwrap();
+    let res = parse(&cli_params, "--life 5");
+    assert!(!res.is_present("--lifetime"));
+    assert_eq!(res.unmatched, vec![String::from("--life"), String::from("5")]);
+}
@@ -0,0 +1,80 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+use clitc::lint::{lint_str, Problem};
+
+fn lint(spec: &str) -> Vec<(String, Option<usize>)> {
+    return lint_str(spec, &[]).into_iter().map(|Problem { pointer, line, .. }| (pointer, line)).collect();
+}
+
+fn problem(pointer: &str, line: usize) -> (String, Option<usize>) {
+    return (pointer.to_string(), Some(line));
+}
+
+const BROKEN: &str = r#"{
+    "name": "tool",
+    "colour": true,
+    "options": [
+        { "short": "-v", "name": "--verbose" },
+        { "short": "-v", "name": "--version" },
+        { "name": "--copy", "params": [
+            { "ord": 0, "name": "files", "type": "array" },
+            { "ord": 1, "name": "dest", "type": "string" },
+            { "ord": 1, "name": "mode", "type": "string" }
+        ] }
+    ]
+}"#;
+
+#[test]
+fn every_problem_is_reported() {
+    let mut problems = lint(BROKEN);
+    problems.sort();
+    assert_eq!(problems, vec![
+        problem("/colour", 3),
+        problem("/options/1/short", 6),
+        problem("/options/2/params/0", 8),
+        problem("/options/2/params/2/ord", 10),
+    ]);
+}
+
+#[test]
+fn problems_name_what_they_conflict_with() {
+    let messages: Vec<String> = lint_str(BROKEN, &[]).iter().map(|x| x.message.clone()).collect();
+    assert!(messages.contains(&String::from("unknown field `colour`")), "{:?}", messages);
+    assert!(messages.contains(&String::from("identifier `-v` is already used at /options/0/short")), "{:?}", messages);
+    assert!(messages.contains(&String::from("--copy: ord 1 is already used at /options/2/params/1")), "{:?}", messages);
+    assert!(messages.iter().any(|x| x.starts_with("--copy: array `files` is not last")), "{:?}", messages);
+}
+
+#[test]
+fn loader_errors_are_located() {
+    // Defaults are checked by the loader, once the spec has no other problems
+    let spec = r#"{
+    "options": [
+        { "name": "--jobs", "params": [
+            { "ord": 0, "name": "n", "type": "int", "default": "many" }
+        ] }
+    ]
+}"#;
+    let problems = lint_str(spec, &[]);
+    assert_eq!(problems.len(), 1);
+    assert_eq!((problems[0].pointer.as_str(), problems[0].line), ("/options/0/params/0/default", Some(4)));
+    assert!(problems[0].message.ends_with("default value `many` is not of type int"), "{}", problems[0].message);
+}
+
+#[test]
+fn custom_types_are_accepted() {
+    let spec = r#"{ "options": [ { "name": "--at", "params": [ { "ord": 0, "name": "when", "type": "date" } ] } ] }"#;
+    assert_eq!(lint(spec), vec![problem("/options/0/params/0/type", 1)]);
+    assert!(lint_str(spec, &[String::from("date")]).is_empty());
+}
+
+#[test]
+fn clean_specs_have_no_problems() {
+    let spec = r#"{ "options": [ { "short": "-c", "name": "--copy", "params": [
+        { "ord": 0, "name": "dest", "type": "string" },
+        { "ord": 1, "name": "files", "type": "array" }
+    ] } ] }"#;
+    assert!(lint(spec).is_empty());
+}
@@ -0,0 +1,94 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::params::CliParameters;
+use clitc::clitc_error::ClitcError;
+
+const TOOL: &str = include_str!("specs/tool.json");
+
+fn same_spec(cli_params: CliParameters) {
+    let expected = CliParameters::from_str(TOOL).unwrap().to_json().unwrap();
+    assert_eq!(cli_params.to_json().unwrap(), expected);
+}
+
+#[test]
+fn json_from_path() {
+    same_spec(CliParameters::from_path("tests/specs/tool.json").unwrap());
+}
+
+#[test]
+fn unsupported_extensions() {
+    match CliParameters::from_path("tests/common/mod.rs") {
+        Err(ClitcError::WrongFormat { path, reason }) => {
+            assert_eq!(path, "tests/common/mod.rs");
+            assert_eq!(reason, "unsupported spec format `rs`");
+        },
+        Err(err) => panic!("{}", err),
+        Ok(_) => panic!("spec was loaded"),
+    }
+}
+
+#[test]
+fn missing_files() {
+    assert!(CliParameters::from_path("tests/specs/missing.json").is_err());
+}
+
+#[cfg(feature = "toml")]
+mod toml {
+    use super::*;
+
+    #[test]
+    fn from_toml_str() {
+        same_spec(CliParameters::from_toml_str(include_str!("specs/tool.toml")).unwrap());
+    }
+
+    #[test]
+    fn toml_from_path() {
+        let cli_params = CliParameters::from_path("tests/specs/tool.toml").unwrap();
+        let res = common::parse(&cli_params, "-v -v a b");
+        assert_eq!(res.get::<i64>("--verbose", "count").unwrap(), Some(2));
+        same_spec(cli_params);
+    }
+
+    #[test]
+    fn toml_is_validated() {
+        let data = "[[options]]\nname = \"--a\"\nshort = \"-a\"\n\n[[options]]\nname = \"--b\"\nshort = \"-a\"\n";
+        assert!(matches!(CliParameters::from_toml_str(data), Err(ClitcError::ConflictingIdentifier { .. })));
+    }
+}
+
+#[cfg(not(feature = "toml"))]
+#[test]
+fn toml_needs_its_feature() {
+    assert!(matches!(CliParameters::from_path("tests/specs/tool.toml"), Err(ClitcError::WrongFormat { .. })));
+}
+
+#[cfg(feature = "yaml")]
+mod yaml {
+    use super::*;
+
+    #[test]
+    fn from_yaml_str() {
+        same_spec(CliParameters::from_yaml_str(include_str!("specs/tool.yaml")).unwrap());
+    }
+
+    #[test]
+    fn yaml_from_path() {
+        same_spec(CliParameters::from_path("tests/specs/tool.yaml").unwrap());
+    }
+
+    #[test]
+    fn yaml_is_validated() {
+        let data = "options:\n  - name: --a\n    params:\n      - { ord: 0, name: x, type: int }\n      - { ord: 0, name: y, type: int }\n";
+        assert!(matches!(CliParameters::from_yaml_str(data), Err(ClitcError::DuplicateOrd { .. })));
+    }
+}
+
+#[cfg(not(feature = "yaml"))]
+#[test]
+fn yaml_needs_its_feature() {
+    assert!(matches!(CliParameters::from_path("tests/specs/tool.yaml"), Err(ClitcError::WrongFormat { .. })));
+}
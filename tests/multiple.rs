@@ -0,0 +1,53 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::params::{
+    CliParameters,
+    TokenStyle,
+};
+use common::{
+    modes,
+    parse,
+    strings,
+};
+
+const SPEC: &str = r#"{ "options": [
+    { "short": "-v", "name": "--verbose", "multiple": "count" },
+    { "short": "-i", "name": "--include", "multiple": true, "params": [ { "ord": 0, "name": "path", "type": "string" } ] },
+    { "short": "-l", "name": "--level", "params": [ { "ord": 0, "name": "n", "type": "int" } ] }
+] }"#;
+
+#[test]
+fn every_occurrence_is_kept() {
+    for cli_params in modes(SPEC) {
+        let res = parse(&cli_params, "--include a -v -i b");
+        assert_eq!(res.values_of::<String>("--include", "path").unwrap(), strings(&["a", "b"]));
+        assert_eq!(res.occurrences("-i").len(), 2);
+        assert_eq!(res.count("--include"), 2);
+    }
+}
+
+#[test]
+fn last_occurrence_wins_by_default() {
+    let res = parse(&CliParameters::from_str(SPEC).unwrap(), "-l 1 -l 2");
+    assert_eq!(res.get::<i64>("--level", "n").unwrap(), Some(2));
+    assert_eq!(res.occurrences("--level").len(), 1);
+}
+
+#[test]
+fn flags_are_counted() {
+    let mut cli_params = CliParameters::from_str(SPEC).unwrap();
+    let res = parse(&cli_params, "-v --verbose -v");
+    assert_eq!(res.count("--verbose"), 3);
+    assert_eq!(res.get::<i64>("--verbose", "count").unwrap(), Some(3));
+
+    cli_params.set_token_style(TokenStyle::Gnu);
+    let res = parse(&cli_params, "-vvv -v");
+    assert_eq!(res.count("-v"), 4);
+
+    let res = parse(&cli_params, "");
+    assert_eq!(res.count("--verbose"), 0);
+}
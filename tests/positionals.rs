@@ -0,0 +1,55 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::clitc_error::ClitcError;
+use common::{
+    modes,
+    parse,
+    parse_err,
+    strings,
+};
+
+const SPEC: &str = r#"{ "options": [
+    { "short": "-v", "name": "--verbose" },
+    { "name": "positional", "params": [ { "ord": 0, "name": "x", "type": "array", "arity": 2 } ] }
+], "positionals": [
+    { "ord": 0, "name": "input", "type": "string" },
+    { "ord": 1, "name": "files", "type": "array", "arity": [2, null] }
+] }"#;
+
+#[test]
+fn positionals_around_options() {
+    for cli_params in modes(SPEC) {
+        let res = parse(&cli_params, "in.txt -v a b c");
+        assert!(res.is_present("--verbose"));
+        assert_eq!(res.positional::<String>("input").unwrap().as_deref(), Some("in.txt"));
+        assert_eq!(res.positional::<Vec<String>>("files").unwrap(), Some(strings(&["a", "b", "c"])));
+    }
+}
+
+#[test]
+fn positional_arity_is_checked() {
+    for cli_params in modes(SPEC) {
+        for (line, count) in [("in.txt", 0), ("in.txt a", 1)] {
+            match parse_err(&cli_params, line) {
+                ClitcError::WrongArity { name, expected, found } => {
+                    assert_eq!((name.as_str(), expected.as_str(), found), ("files", "at least 2", count), "{}", line);
+                },
+                err => panic!("`{}`: {}", line, err),
+            }
+        }
+    }
+}
+
+#[test]
+fn options_named_positional() {
+    for cli_params in modes(SPEC) {
+        match parse_err(&cli_params, "positional q") {
+            ClitcError::WrongArity { name, .. } => assert_eq!(name, "positional x"),
+            err => panic!("{}", err),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+extern crate serde_json;
+
+mod common;
+
+use clitc::params::{CliParameters, ConfigFile, Layer, ParamValue, ParseResult, Source};
+use common::args;
+use serde_json::json;
+
+const SPEC: &str = r#"{
+    "options": [
+        { "short": "-l", "name": "--lifetime", "params": [
+            { "ord": 0, "name": "secs", "type": "int", "env": "CLITC_TEST_SOURCES_SECS" },
+            { "ord": 1, "name": "expected_val", "type": "num", "default": 1.5 }
+        ] },
+        { "short": "-v", "name": "--verbose" },
+        { "name": "--name", "params": [ { "ord": 0, "name": "n", "type": "string" } ] }
+    ],
+    "positionals": [ { "ord": 0, "name": "file", "type": "string", "default": "out.txt" } ]
+}"#;
+
+fn layered(line: &str) -> ParseResult {
+    std::env::set_var("CLITC_TEST_SOURCES_SECS", "30");
+    let cli_params = CliParameters::from_str(SPEC).unwrap();
+    let config = ConfigFile::from_value("tool.json", json!({ "name": "demo", "lifetime": { "secs": 10 } })).unwrap();
+    return cli_params.parse_vec_layered(args(line), Some(&config), &[Layer::Cli, Layer::Env, Layer::Config]).unwrap();
+}
+
+#[test]
+fn values_carry_their_source() {
+    let res = layered("--name x -l 5 2 in.txt");
+    assert!(matches!(res.sourced("-l", "secs"), Some((ParamValue::Int(5), Source::Cli(3)))));
+    assert!(matches!(res.sourced("--lifetime", "expected_val"), Some((ParamValue::Num(n), Source::Cli(4))) if *n == 2.0));
+    assert!(matches!(res.sourced("name", "n"), Some((ParamValue::String(n), Source::Cli(1))) if n == "x"));
+    assert_eq!(res.positional_source("file"), Some(&Source::Cli(5)));
+    assert!(res.sourced("--verbose", "n").is_none());
+    assert!(res.sourced("--lifetime", "missing").is_none());
+}
+
+#[test]
+fn values_from_other_layers_carry_their_source() {
+    let res = layered("-v");
+    assert!(matches!(res.sourced("--lifetime", "secs"), Some((ParamValue::Int(30), Source::Env(var))) if var == "CLITC_TEST_SOURCES_SECS"));
+    assert!(matches!(res.sourced("--lifetime", "expected_val"), Some((ParamValue::Num(_), Source::Default))));
+    match res.sourced("--name", "n") {
+        Some((ParamValue::String(n), Source::Config { file, key })) => assert_eq!((n.as_str(), file.as_str(), key.as_str()), ("demo", "tool.json", "name")),
+        _ => panic!("--name was not read from the config file"),
+    }
+    assert_eq!(res.positional_source("file"), Some(&Source::Default));
+}
+
+#[test]
+fn effective_config_lists_every_value_with_its_source() {
+    let expected = vec![
+        "--lifetime expected_val = 1.5      # default",
+        "--lifetime secs         = 30       # env CLITC_TEST_SOURCES_SECS",
+        "--name n                = demo     # config tool.json (name)",
+        "--verbose               = true     # command line (arg 0)",
+        "file                    = out.txt  # default",
+    ];
+    assert_eq!(layered("-v").effective_config(), expected);
+
+    let expected = vec![
+        "--lifetime expected_val = 2       # command line (arg 4)",
+        "--lifetime secs         = 5       # command line (arg 3)",
+        "--name n                = x       # command line (arg 1)",
+        "file                    = in.txt  # command line (arg 5)",
+    ];
+    assert_eq!(layered("--name x -l 5 2 in.txt").effective_config(), expected);
+}
+
+#[test]
+fn empty_effective_config() {
+    let cli_params = CliParameters::from_str(r#"{ "options": [ { "name": "--verbose" } ] }"#).unwrap();
+    assert!(cli_params.parse_vec(Vec::new()).unwrap().effective_config().is_empty());
+}
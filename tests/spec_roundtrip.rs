@@ -0,0 +1,28 @@
+extern crate clitc;
+extern crate serde_json;
+
+use clitc::params::CliParameters;
+use serde_json::Value;
+
+const DEMO_SPEC: &str = include_str!("../src/demo_spec.json");
+
+#[test]
+fn demo_spec_round_trip() {
+    let cli_params = CliParameters::from_str(DEMO_SPEC).expect("Could not load demo spec");
+    let json = cli_params.to_json().expect("Could not write spec");
+
+    // Written spec describes the same options as the original
+    let original: Value = serde_json::from_str(DEMO_SPEC).unwrap();
+    let written: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(original, written);
+
+    // and can be loaded again
+    let reloaded = CliParameters::from_str(&json).expect("Could not reload written spec");
+    assert_eq!(reloaded.to_json().unwrap(), json);
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    let data = r#"{ "options": [ { "name": "--verbose", "descrption": "typo" } ] }"#;
+    assert!(CliParameters::from_str(data).is_err());
+}
@@ -0,0 +1,104 @@
+#![allow(clippy::needless_return)]
+
+extern crate clitc;
+
+mod common;
+
+use clitc::params::TokenStyle;
+use common::{
+    modes,
+    parse,
+    strings,
+};
+
+const SPEC: &str = r#"{ "name": "svc", "options": [
+    { "short": "-v", "name": "--verbose" },
+    { "short": "-c", "name": "--config", "params": [ { "ord": 0, "name": "path", "type": "string" } ] }
+], "subcommands": [
+    { "name": "db", "options": [
+        { "short": "-u", "name": "--url", "params": [ { "ord": 0, "name": "url", "type": "string" } ] }
+    ], "subcommands": [
+        { "name": "migrate", "options": [
+            { "name": "--dry-run" },
+            { "short": "-c", "name": "--count", "params": [ { "ord": 0, "name": "n", "type": "int" } ] }
+        ], "positionals": [ { "ord": 0, "name": "target", "type": "string", "required": false } ] }
+    ] },
+    { "name": "serve", "options": [
+        { "short": "-t", "name": "--tags", "params": [ { "ord": 0, "name": "tags", "type": "array" } ] }
+    ] }
+] }"#;
+
+#[test]
+fn command_path_is_returned() {
+    for cli_params in modes(SPEC) {
+        let res = parse(&cli_params, "db migrate --dry-run v2");
+        assert_eq!(res.command, strings(&["db", "migrate"]));
+        assert!(res.is_present("--dry-run"));
+        assert_eq!(res.positional::<String>("target").unwrap().as_deref(), Some("v2"));
+        assert!(res.unmatched.is_empty());
+
+        let res = parse(&cli_params, "-v");
+        assert!(res.command.is_empty());
+        assert!(res.is_present("--verbose"));
+    }
+}
+
+#[test]
+fn parent_options_before_command_names() {
+    for cli_params in modes(SPEC) {
+        for line in ["-v db migrate --dry-run", "db -v migrate --dry-run", "-c svc.toml db -u pg://x migrate --dry-run -v"] {
+            let res = parse(&cli_params, line);
+            assert_eq!(res.command, strings(&["db", "migrate"]), "{}", line);
+            assert!(res.is_present("--verbose"), "{}", line);
+            assert!(res.is_present("--dry-run"), "{}", line);
+            assert!(res.unmatched.is_empty(), "{}", line);
+        }
+        let res = parse(&cli_params, "-c svc.toml db -u pg://x migrate -v");
+        assert_eq!(res.get::<String>("--config", "path").unwrap().as_deref(), Some("svc.toml"));
+        assert_eq!(res.get::<String>("--url", "url").unwrap().as_deref(), Some("pg://x"));
+    }
+}
+
+#[test]
+fn subcommand_options_shadow_parent_options() {
+    for cli_params in modes(SPEC) {
+        let res = parse(&cli_params, "db migrate -c 3");
+        assert_eq!(res.get::<i64>("--count", "n").unwrap(), Some(3));
+        assert!(!res.is_present("--config"));
+        let res = parse(&cli_params, "db migrate --config svc.toml");
+        assert_eq!(res.get::<String>("--config", "path").unwrap().as_deref(), Some("svc.toml"));
+    }
+}
+
+#[test]
+fn option_values_are_not_command_names() {
+    for cli_params in modes(SPEC) {
+        // The value of `--config` is taken before looking for a subcommand
+        let res = parse(&cli_params, "-c db serve");
+        assert_eq!(res.command, strings(&["serve"]));
+        assert_eq!(res.get::<String>("--config", "path").unwrap().as_deref(), Some("db"));
+        // Arrays stop at a subcommand name
+        let res = parse(&cli_params, "serve -t a b");
+        assert_eq!(res.get::<Vec<String>>("--tags", "tags").unwrap(), Some(strings(&["a", "b"])));
+    }
+}
+
+#[test]
+fn subcommands_with_gnu_tokens() {
+    for mut cli_params in modes(SPEC) {
+        cli_params.set_token_style(TokenStyle::Gnu);
+        let res = parse(&cli_params, "--config=svc.toml db -vu pg://x migrate --count=2");
+        assert_eq!(res.command, strings(&["db", "migrate"]));
+        assert_eq!(res.get::<String>("--config", "path").unwrap().as_deref(), Some("svc.toml"));
+        assert_eq!(res.get::<String>("--url", "url").unwrap().as_deref(), Some("pg://x"));
+        assert_eq!(res.get::<i64>("--count", "n").unwrap(), Some(2));
+        assert!(res.is_present("--verbose"));
+    }
+}
+
+#[test]
+fn positionals_end_the_command_path() {
+    let res = parse(&modes(SPEC)[0], "db migrate v2 serve");
+    assert_eq!(res.command, strings(&["db", "migrate"]));
+    assert_eq!(res.unmatched, strings(&["serve"]));
+}